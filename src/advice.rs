@@ -0,0 +1,169 @@
+use std::fmt;
+
+use crate::catalog::{Bundle, Catalog, CatalogEntry, Channel};
+use crate::labels::label;
+
+/// What a deprecated bundle should move to, worked out from the catalog
+#[derive(Debug, PartialEq)]
+pub enum Remediation {
+    /// The whole package is deprecated, its message usually names the
+    /// operator replacing it
+    Message(String),
+    /// Upgrade to the head of a channel
+    Upgrade {
+        channel: String,
+        head: String,
+        version: Option<String>,
+    },
+    /// No target in the catalog, with the reason
+    Unavailable(String),
+}
+
+impl fmt::Display for Remediation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Remediation::Message(message) => write!(f, "{}", message),
+            Remediation::Upgrade {
+                channel,
+                head,
+                version,
+            } => {
+                write!(f, "upgrade to {}", head)?;
+                if let Some(version) = version {
+                    write!(f, " ({})", version)?;
+                }
+                write!(f, ", the head of channel {}", channel)
+            }
+            Remediation::Unavailable(reason) => write!(f, "none, {}", reason),
+        }
+    }
+}
+
+/// Deprecations affecting a bundle and what to do about them
+#[derive(Debug, Default)]
+pub struct Advice {
+    /// One line per deprecated package, channel or bundle, with its message
+    pub deprecated: Vec<String>,
+    /// Distinct remediations over the channels of the bundle
+    pub remediations: Vec<Remediation>,
+}
+
+fn upgrade(catalog: &Catalog, channel: &Channel) -> Option<Remediation> {
+    let head = channel.head()?;
+    Some(Remediation::Upgrade {
+        channel: channel.name.clone(),
+        head: head.name.clone(),
+        version: catalog
+            .version_of(&channel.package, &head.name)
+            .map(|version| version.to_string()),
+    })
+}
+
+/// Head of the default channel, for bundles of a deprecated channel
+fn default_channel_head(catalog: &Catalog, bundle: &Bundle) -> Remediation {
+    let default_channel = match catalog.packages.get(&bundle.package) {
+        Some(CatalogEntry::OlmPackage(package)) => package.default_channel.as_deref(),
+        _ => None,
+    };
+    let Some(default_channel) = default_channel else {
+        return Remediation::Unavailable(format!(
+            "package {} has no default channel",
+            bundle.package
+        ));
+    };
+    let deprecations = catalog.deprecations_of(&bundle.package);
+    if deprecations
+        .is_some_and(|deprecations| deprecations.channel_message(default_channel).is_some())
+    {
+        return Remediation::Unavailable(format!(
+            "the default channel {} is deprecated too",
+            default_channel
+        ));
+    }
+    catalog
+        .channel(&bundle.package, default_channel)
+        .and_then(|channel| upgrade(catalog, channel))
+        .unwrap_or_else(|| {
+            Remediation::Unavailable(format!(
+                "the head of the default channel {} can't be determined",
+                default_channel
+            ))
+        })
+}
+
+/// Advice for a bundle, None when neither it nor its channels or package
+/// are deprecated. A deprecated package wins over channel deprecations,
+/// which win over the bundle's own.
+pub fn advise(catalog: &Catalog, bundle: &Bundle) -> Option<Advice> {
+    let deprecations = catalog.deprecations_of(&bundle.package)?;
+    let mut advice = Advice::default();
+
+    if let Some(message) = deprecations.package_message() {
+        advice
+            .deprecated
+            .push(format!("package {}: {}", bundle.package, message));
+        advice
+            .remediations
+            .push(Remediation::Message(message.to_string()));
+        return Some(advice);
+    }
+
+    let bundle_message = deprecations.bundle_message(&bundle.name);
+    if let Some(message) = bundle_message {
+        advice
+            .deprecated
+            .push(format!("bundle {}: {}", bundle.name, message));
+    }
+    for channel in catalog.channels_of(&bundle.package) {
+        if channel.entry(&bundle.name).is_none() {
+            continue;
+        }
+        let remediation = if let Some(message) = deprecations.channel_message(&channel.name) {
+            advice
+                .deprecated
+                .push(format!("channel {}: {}", channel.name, message));
+            default_channel_head(catalog, bundle)
+        } else if bundle_message.is_some() {
+            match upgrade(catalog, channel) {
+                Some(Remediation::Upgrade { head, .. }) if head == bundle.name => {
+                    Remediation::Unavailable(format!("it is the head of channel {}", channel.name))
+                }
+                Some(remediation) => remediation,
+                None => Remediation::Unavailable(format!(
+                    "the head of channel {} can't be determined",
+                    channel.name
+                )),
+            }
+        } else {
+            continue;
+        };
+        if !advice.remediations.contains(&remediation) {
+            advice.remediations.push(remediation);
+        }
+    }
+
+    if advice.deprecated.is_empty() {
+        return None;
+    }
+    if advice.remediations.is_empty() {
+        advice.remediations.push(Remediation::Unavailable(format!(
+            "the bundle isn't in any channel of {}",
+            bundle.package
+        )));
+    }
+    Some(advice)
+}
+
+impl fmt::Display for Advice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", label("deprecated"))?;
+        for line in &self.deprecated {
+            writeln!(f, "- {}", line)?;
+        }
+        write!(f, "{}:", label("recommended"))?;
+        for remediation in &self.remediations {
+            write!(f, "\n- {}", remediation)?;
+        }
+        Ok(())
+    }
+}
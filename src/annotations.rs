@@ -0,0 +1,104 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::catalog::{Bundle, Catalog};
+
+/// Annotations compared when no `--annotation` is given, the full set is huge
+pub const DEFAULT_ANNOTATIONS: &[&str] = &[
+    "operators.openshift.io/infrastructure-features",
+    "operators.openshift.io/valid-subscription",
+    "features.operators.openshift.io/disconnected",
+    "features.operators.openshift.io/fips-compliant",
+    "features.operators.openshift.io/proxy-aware",
+    "features.operators.openshift.io/tls-profiles",
+    "features.operators.openshift.io/token-auth-aws",
+    "features.operators.openshift.io/token-auth-azure",
+    "features.operators.openshift.io/token-auth-gcp",
+];
+
+/// Value of an annotation on one bundle
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Cell {
+    pub value: Option<String>,
+    /// Differs from the value on the previous version
+    pub changed: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Row {
+    pub key: String,
+    pub values: Vec<Cell>,
+}
+
+/// Annotation values by bundle, in version order
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AnnotationMatrix {
+    /// Bundle versions, or names when the version can't be resolved
+    pub columns: Vec<String>,
+    pub rows: Vec<Row>,
+}
+
+pub fn matrix(catalog: &Catalog, package: &str, keys: &[String]) -> AnnotationMatrix {
+    let mut bundles: Vec<&Bundle> = catalog.bundles_of(package).collect();
+    let version_of = |bundle: &Bundle| catalog.version_of(&bundle.package, &bundle.name);
+    bundles.sort_by(|a, b| (version_of(a), &a.name).cmp(&(version_of(b), &b.name)));
+
+    let columns = bundles
+        .iter()
+        .map(|bundle| {
+            version_of(bundle)
+                .map(|version| version.to_string())
+                .unwrap_or_else(|| bundle.name.clone())
+        })
+        .collect();
+
+    let rows = keys
+        .iter()
+        .map(|key| {
+            let mut previous: Option<Option<&str>> = None;
+            let values = bundles
+                .iter()
+                .map(|bundle| {
+                    let value = bundle.csv_annotation(key);
+                    let changed = previous.is_some_and(|previous| previous != value);
+                    previous = Some(value);
+                    Cell {
+                        value: value.map(String::from),
+                        changed,
+                    }
+                })
+                .collect();
+            Row {
+                key: key.clone(),
+                values,
+            }
+        })
+        .collect();
+
+    AnnotationMatrix { columns, rows }
+}
+
+impl AnnotationMatrix {
+    /// Header and rows for text and CSV output, changed values are marked with `*`
+    pub fn table(&self) -> (Vec<&str>, Vec<Vec<String>>) {
+        let mut headers = vec!["annotation"];
+        headers.extend(self.columns.iter().map(String::as_str));
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut cells = vec![row.key.clone()];
+                cells.extend(row.values.iter().map(|cell| {
+                    let value = cell.value.as_deref().unwrap_or("-");
+                    if cell.changed {
+                        format!("{} *", value)
+                    } else {
+                        value.to_string()
+                    }
+                }));
+                cells
+            })
+            .collect();
+        (headers, rows)
+    }
+}
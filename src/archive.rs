@@ -0,0 +1,78 @@
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path};
+
+use crate::loader::is_catalog_file;
+
+const ARCHIVE_SUFFIXES: &[&str] = &[
+    ".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tbz2", ".tar.xz", ".txz", ".tar.zst",
+];
+
+/// Whether a --file value names a tar archive, compressed or not
+pub fn is_archive(path: &str) -> bool {
+    let lowercase = path.to_ascii_lowercase();
+    ARCHIVE_SUFFIXES
+        .iter()
+        .any(|suffix| lowercase.ends_with(suffix))
+}
+
+/// Compression recognized from the first bytes of the archive
+fn compression(start: &[u8]) -> Option<&'static str> {
+    match start {
+        [0x1F, 0x8B, ..] => Some("gzip"),
+        [b'B', b'Z', b'h', ..] => Some("bzip2"),
+        [0xFD, b'7', b'z', b'X', b'Z', 0x00, ..] => Some("xz"),
+        [0x28, 0xB5, 0x2F, 0xFD, ..] => Some("zstd"),
+        _ => None,
+    }
+}
+
+/// Catalog files of a tar or tar.gz archive with their content, read
+/// without extracting anything. Entries are picked like the files of a
+/// directory: hidden ones and ones without a catalog extension are skipped.
+/// Each is labeled with the archive path followed by its path in the
+/// archive, the labels are sorted.
+pub fn catalog_files(path: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let failed = |err: std::io::Error| format!("Failed to read archive {}: {}", path, err);
+    let mut reader = BufReader::new(File::open(path).map_err(failed)?);
+    let reader: Box<dyn Read> = match compression(reader.fill_buf().map_err(failed)?) {
+        None => Box::new(reader),
+        Some("gzip") => Box::new(GzDecoder::new(reader)),
+        Some(other) => {
+            return Err(format!(
+                "Failed to read archive {}: {} compression isn't supported, only tar and tar.gz",
+                path, other
+            ))
+        }
+    };
+
+    let mut files = Vec::new();
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(failed)? {
+        let mut entry = entry.map_err(failed)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_path = entry.path().map_err(failed)?.into_owned();
+        let hidden = entry_path.components().any(|component| match component {
+            Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+            _ => false,
+        });
+        if hidden || !is_catalog_file(&entry_path) {
+            continue;
+        }
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).map_err(failed)?;
+        let inner = entry_path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect::<std::path::PathBuf>();
+        files.push((
+            Path::new(path).join(inner).to_string_lossy().into_owned(),
+            content,
+        ));
+    }
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(files)
+}
@@ -0,0 +1,55 @@
+use crate::warnings::{self, Warning};
+
+/// Exit code of batch commands that processed everything but had some items fail
+pub const PARTIAL_FAILURE: i32 = 5;
+
+/// Outcome of a command working through many items. Failures are reported as
+/// they happen and the command carries on with the next item.
+pub struct Batch {
+    /// Plural of what the items are, e.g. `packages`
+    what: &'static str,
+    succeeded: usize,
+    failed: usize,
+}
+
+impl Batch {
+    pub fn new(what: &'static str) -> Batch {
+        Batch {
+            what,
+            succeeded: 0,
+            failed: 0,
+        }
+    }
+
+    pub fn succeed(&mut self) {
+        self.succeeded += 1;
+    }
+
+    pub fn fail(&mut self, item: &str, reason: &str) {
+        warnings::warn(Warning::new("item-failed", format!("{}: {}", item, reason)));
+        self.failed += 1;
+    }
+
+    /// Whether there were items and none of them succeeded
+    pub fn all_failed(&self) -> bool {
+        self.succeeded == 0 && self.failed > 0
+    }
+
+    /// Print the counts. Exits with PARTIAL_FAILURE when only some items
+    /// failed, it's an error when all of them did.
+    pub fn finish(self) -> Result<(), String> {
+        let total = self.succeeded + self.failed;
+        warnings::warn(Warning::new(
+            "batch-summary",
+            format!(
+                "{} of {} {} succeeded, {} failed",
+                self.succeeded, total, self.what, self.failed
+            ),
+        ));
+        match (self.succeeded, self.failed) {
+            (_, 0) => Ok(()),
+            (0, _) => Err(format!("all {} {} failed", total, self.what)),
+            _ => warnings::exit(PARTIAL_FAILURE),
+        }
+    }
+}
@@ -0,0 +1,776 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::labels::label;
+use crate::resolve::{resolve, ResolvedVersion, VersionScheme};
+use crate::timestamp::Timestamp;
+use crate::version::{Version, VersionRange};
+
+/// YAML Document Types
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(tag = "schema")]
+pub enum CatalogEntry {
+    #[serde(rename = "olm.package")]
+    OlmPackage(Package),
+    #[serde(rename = "olm.channel")]
+    OpmChannel(Channel),
+    #[serde(rename = "olm.bundle")]
+    OlmBundle(Bundle),
+    #[serde(rename = "olm.deprecations")]
+    OlmDeprecations(Deprecations),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Package {
+    pub name: String,
+    #[serde(
+        rename = "defaultChannel",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub icon: Option<serde_yaml::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<Property>,
+}
+
+impl std::fmt::Display for Package {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", label("package"), self.name)?;
+        if let Some(channel) = &self.default_channel {
+            write!(f, "\n  {}: {}", label("default_channel"), channel)?;
+        }
+        if let Some(description) = &self.description {
+            write!(
+                f,
+                "\n  {}: {}",
+                label("description"),
+                description.trim_end().replace('\n', "\n    ")
+            )?;
+        }
+        if let Some(icon) = &self.icon {
+            let media_type = icon
+                .get("mediatype")
+                .and_then(|media_type| media_type.as_str())
+                .unwrap_or("unknown media type");
+            write!(f, "\n  {}: {}", label("icon"), media_type)?;
+        }
+        write_properties(f, &self.properties)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ChannelEntry {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub replaces: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skips: Vec<String>,
+    #[serde(rename = "skipRange", skip_serializing_if = "Option::is_none")]
+    pub skip_range: Option<String>,
+}
+
+impl ChannelEntry {
+    /// Whether OLM offers this entry as an upgrade from the given bundle
+    pub fn upgrades_from(&self, name: &str, version: Option<&Version>) -> bool {
+        if self.replaces == name || self.skips.iter().any(|skip| skip == name) {
+            return true;
+        }
+        match (&self.skip_range, version) {
+            (Some(range), Some(version)) => VersionRange::parse(range)
+                .map(|range| range.matches(version))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Channel {
+    pub name: String,
+    pub package: String,
+    pub entries: Vec<ChannelEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<Property>,
+    /// Vendor specific top-level keys, kept so they survive rewrites
+    #[serde(flatten)]
+    #[schemars(with = "serde_json::Map<String, serde_json::Value>")]
+    pub extra: serde_yaml::Mapping,
+}
+
+impl Channel {
+    /// Entries that no other entry replaces or skips. An entry named in any
+    /// `skips` list is never a head, while a `skipRange` covering it doesn't
+    /// count as an incoming edge. Entries replacing or skipping themselves
+    /// aren't heads either. Not yet checked against opm's output, see
+    /// docs/follow-ups.md.
+    pub fn heads(&self) -> Vec<&ChannelEntry> {
+        self.entries
+            .iter()
+            .filter(|candidate| {
+                !self.entries.iter().any(|entry| {
+                    entry.replaces == candidate.name || entry.skips.contains(&candidate.name)
+                })
+            })
+            .collect()
+    }
+
+    /// The channel head, only when it is unambiguous
+    pub fn head(&self) -> Option<&ChannelEntry> {
+        match self.heads().as_slice() {
+            [head] => Some(head),
+            _ => None,
+        }
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&ChannelEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Follow replaces edges back from `head`, returning entries from the tail to the head
+    pub fn replaces_chain<'a>(&'a self, head: &'a ChannelEntry) -> Vec<&'a ChannelEntry> {
+        let mut chain = vec![head];
+        let mut current = head;
+        while let Some(previous) = self.entry(&current.replaces) {
+            // Guard against replaces cycles
+            if chain.iter().any(|entry| entry.name == previous.name) {
+                break;
+            }
+            chain.push(previous);
+            current = previous;
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Entries in the order OLM walks them. With several head candidates the
+    /// longest replaces chain is taken as the main one.
+    pub fn upgrade_chain(&self) -> UpgradeChain<'_> {
+        let chain = self
+            .heads()
+            .into_iter()
+            .map(|head| self.replaces_chain(head))
+            .fold(Vec::new(), |longest, chain| {
+                if chain.len() > longest.len() {
+                    chain
+                } else {
+                    longest
+                }
+            });
+        let off_chain = self
+            .entries
+            .iter()
+            .filter(|entry| !chain.iter().any(|on_chain| on_chain.name == entry.name))
+            .collect();
+
+        UpgradeChain { chain, off_chain }
+    }
+}
+
+/// Channel entries split into the main replaces chain and everything else
+pub struct UpgradeChain<'a> {
+    /// Entries from the tail to the head
+    pub chain: Vec<&'a ChannelEntry>,
+    /// Entries reachable only via skips or not connected at all
+    pub off_chain: Vec<&'a ChannelEntry>,
+}
+
+fn write_entry(f: &mut std::fmt::Formatter<'_>, entry: &ChannelEntry) -> std::fmt::Result {
+    write!(f, "\n    - {}", entry.name)?;
+    if !entry.replaces.is_empty() {
+        write!(f, "\n      {}: {}", label("replaces"), entry.replaces)?;
+    }
+    if !entry.skips.is_empty() {
+        write!(f, "\n      {}: {:?}", label("skips"), entry.skips)?;
+    }
+    if let Some(range) = &entry.skip_range {
+        write!(f, "\n      {}: {}", label("skip_range"), range)?;
+    }
+    Ok(())
+}
+
+// Implement a custom fucntion for Channel to print the entries
+impl std::fmt::Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}\n  {}: {}\n  {}:",
+            label("channel"),
+            self.name,
+            label("package"),
+            self.package,
+            label("entries")
+        )?;
+        for entry in &self.entries {
+            write_entry(f, entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Channel printed in upgrade order instead of file order
+pub struct OrderedChannel<'a>(pub &'a Channel);
+
+impl std::fmt::Display for OrderedChannel<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let channel = self.0;
+        let upgrade_chain = channel.upgrade_chain();
+        write!(
+            f,
+            "{}: {}\n  {}: {}\n  {}:",
+            label("channel"),
+            channel.name,
+            label("package"),
+            channel.package,
+            label("entries")
+        )?;
+        for entry in &upgrade_chain.chain {
+            write_entry(f, entry)?;
+        }
+        if !upgrade_chain.off_chain.is_empty() {
+            write!(f, "\n  {}:", label("off_chain_entries"))?;
+            for entry in &upgrade_chain.off_chain {
+                write_entry(f, entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, JsonSchema)]
+pub struct Property {
+    #[schemars(rename = "type")]
+    pub property_type: String,
+    #[schemars(with = "serde_json::Value")]
+    pub value: serde_yaml::Value,
+    /// Why the property doesn't have the shape its type calls for. Malformed
+    /// properties are kept as written and left out of typed lookups.
+    #[schemars(skip)]
+    pub malformed: Option<String>,
+    /// The property as written, for malformed ones
+    #[schemars(skip)]
+    raw: Option<serde_yaml::Value>,
+}
+
+/// Properties as `type: value` lines, values as compact JSON. Malformed
+/// ones say what's wrong with them.
+fn write_properties(f: &mut std::fmt::Formatter<'_>, properties: &[Property]) -> std::fmt::Result {
+    if properties.is_empty() {
+        return Ok(());
+    }
+    write!(f, "\n  {}:", label("properties"))?;
+    for property in properties {
+        write!(
+            f,
+            "\n    - {}: {}",
+            property.display_type(),
+            serde_json::to_string(&property.value).unwrap_or_default()
+        )?;
+        if let Some(reason) = &property.malformed {
+            write!(f, " ({}: {})", label("malformed"), reason)?;
+        }
+    }
+    Ok(())
+}
+
+impl Property {
+    /// Type for messages, malformed properties may have none
+    pub fn display_type(&self) -> &str {
+        if self.property_type.is_empty() {
+            "no type"
+        } else {
+            &self.property_type
+        }
+    }
+}
+
+/// Fields a known property type needs in its value, all strings
+fn required_fields(property_type: &str) -> &'static [&'static str] {
+    match property_type {
+        "olm.package" => &["packageName", "version"],
+        "olm.package.required" => &["packageName", "versionRange"],
+        "olm.gvk" | "olm.gvk.required" => &["group", "kind", "version"],
+        "olm.channel" => &["channelName"],
+        _ => &[],
+    }
+}
+
+/// What is wrong with the value of a property of a known type
+fn shape_problem(property_type: &str, value: &serde_yaml::Value) -> Option<String> {
+    match property_type {
+        "olm.maxOpenShiftVersion" => {
+            return (!(value.is_string() || value.is_number()))
+                .then(|| "value isn't a string or a number".to_string());
+        }
+        "olm.csv.metadata" => {
+            return (!value.is_mapping()).then(|| "value isn't a mapping".to_string());
+        }
+        "olm.bundle.object" => {
+            let field = |name| value.get(name).is_some_and(serde_yaml::Value::is_string);
+            return (!(field("data") || field("ref")))
+                .then(|| "value has neither a data nor a ref string".to_string());
+        }
+        _ => {}
+    }
+    let fields = required_fields(property_type);
+    if fields.is_empty() {
+        return None;
+    }
+    if !value.is_mapping() {
+        return Some("value isn't a mapping".to_string());
+    }
+    fields
+        .iter()
+        .find(|field| !value.get(**field).is_some_and(serde_yaml::Value::is_string))
+        .map(|field| format!("value has no {} string", field))
+}
+
+/// Properties never fail the bundle they belong to: one without a type or
+/// value, or with a value its type doesn't allow, is marked malformed instead
+impl<'de> Deserialize<'de> for Property {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut raw = serde_yaml::Value::deserialize(deserializer)?;
+        let property_type = raw
+            .get("type")
+            .and_then(serde_yaml::Value::as_str)
+            .map(String::from);
+        let malformed = match (&property_type, raw.get("value")) {
+            _ if !raw.is_mapping() => Some("not a mapping".to_string()),
+            (None, _) => Some("no type string".to_string()),
+            (Some(_), None) => Some("no value".to_string()),
+            (Some(property_type), Some(value)) => shape_problem(property_type, value),
+        };
+        let value = match (&malformed, raw.as_mapping_mut()) {
+            (None, Some(mapping)) => mapping.remove("value").unwrap_or_default(),
+            _ => raw.get("value").cloned().unwrap_or_default(),
+        };
+        Ok(Property {
+            property_type: property_type.unwrap_or_default(),
+            value,
+            raw: malformed.is_some().then_some(raw),
+            malformed,
+        })
+    }
+}
+
+/// Well-formed properties are written as `type` and `value`, malformed ones
+/// exactly as read
+impl Serialize for Property {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Fields<'a> {
+            #[serde(rename = "type")]
+            property_type: &'a str,
+            value: &'a serde_yaml::Value,
+        }
+        match &self.raw {
+            Some(raw) => raw.serialize(serializer),
+            None => Fields {
+                property_type: &self.property_type,
+                value: &self.value,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RelatedImage {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    pub image: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Bundle {
+    pub name: String,
+    pub package: String,
+    pub image: String,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+    #[serde(
+        rename = "relatedImages",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub related_images: Vec<RelatedImage>,
+}
+
+impl Bundle {
+    /// First well-formed property of the given type
+    pub fn property(&self, property_type: &str) -> Option<&Property> {
+        self.properties.iter().find(|property| {
+            property.property_type == property_type && property.malformed.is_none()
+        })
+    }
+
+    /// Annotation of the bundle's CSV from the olm.csv.metadata property
+    pub fn csv_annotation(&self, key: &str) -> Option<&str> {
+        self.property("olm.csv.metadata")?
+            .value
+            .get("annotations")?
+            .get(key)?
+            .as_str()
+    }
+
+    /// When the bundle was built, from the `createdAt` CSV annotation
+    pub fn created_at(&self) -> Option<Timestamp> {
+        Timestamp::parse(self.csv_annotation("createdAt")?)
+    }
+
+    /// Version of the bundle taken from its olm.package property
+    pub fn version(&self) -> Option<Version> {
+        self.property("olm.package")
+            .and_then(|property| property.value.get("version"))
+            .and_then(|version| version.as_str())
+            .and_then(|version| Version::parse(version).ok())
+    }
+
+    /// Highest OpenShift version the bundle can be installed on
+    pub fn max_openshift_version(&self) -> Option<String> {
+        let value = &self.property("olm.maxOpenShiftVersion")?.value;
+        match value {
+            serde_yaml::Value::String(version) => Some(version.clone()),
+            serde_yaml::Value::Number(version) => Some(version.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Version encoded in the bundle name by the `<name>.vX.Y.Z` convention
+    pub fn name_version(&self) -> Option<Version> {
+        version_from_name(&self.name)
+    }
+}
+
+/// Parse the version out of a `<name>.vX.Y.Z` bundle name
+pub fn version_from_name(name: &str) -> Option<Version> {
+    name.match_indices(".v")
+        .find_map(|(index, _)| Version::parse(&name[index + 2..]).ok())
+}
+
+// Create a function for Bundle struct to implement the Display trait
+impl std::fmt::Display for Bundle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}\n  {}: {}\n  {}: {}",
+            label("bundle"),
+            self.name,
+            label("image"),
+            self.image,
+            label("package"),
+            self.package
+        )?;
+        write_properties(f, &self.properties)?;
+        if !self.related_images.is_empty() {
+            write!(f, "\n  {}:", label("related_images"))?;
+            for related in &self.related_images {
+                if related.name.is_empty() {
+                    write!(f, "\n    - {}", related.image)?;
+                } else {
+                    write!(f, "\n    - {}: {}", related.name, related.image)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct DeprecationReference {
+    pub schema: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct DeprecationEntry {
+    pub reference: DeprecationReference,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Deprecations {
+    pub package: String,
+    #[serde(default)]
+    pub entries: Vec<DeprecationEntry>,
+}
+
+impl Deprecations {
+    fn message(&self, schema: &str, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.reference.schema == schema && entry.reference.name == name)
+            .map(|entry| entry.message.as_str())
+    }
+
+    /// Deprecation message of the whole package
+    pub fn package_message(&self) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.reference.schema == "olm.package")
+            .map(|entry| entry.message.as_str())
+    }
+
+    pub fn channel_message(&self, channel: &str) -> Option<&str> {
+        self.message("olm.channel", channel)
+    }
+
+    pub fn bundle_message(&self, bundle: &str) -> Option<&str> {
+        self.message("olm.bundle", bundle)
+    }
+}
+
+/// Catalog content organized by package
+pub struct Catalog {
+    pub packages: HashMap<String, CatalogEntry>,
+    pub channels: HashMap<String, Vec<CatalogEntry>>,
+    pub bundles: HashMap<String, Vec<CatalogEntry>>,
+    pub deprecations: HashMap<String, CatalogEntry>,
+    /// Version resolution strategies allowed for sorting and analysis
+    pub version_scheme: VersionScheme,
+}
+
+impl Catalog {
+    /// Organize loaded documents by package
+    pub fn from_entries(entries: Vec<CatalogEntry>) -> Catalog {
+        // Organize data into a HashMap of packages
+        let mut packages: HashMap<String, CatalogEntry> = HashMap::new();
+        let mut channels: HashMap<String, Vec<CatalogEntry>> = HashMap::new();
+        let mut bundles: HashMap<String, Vec<CatalogEntry>> = HashMap::new();
+        let mut deprecations: HashMap<String, CatalogEntry> = HashMap::new();
+
+        for entry in entries {
+            match &entry {
+                CatalogEntry::OlmPackage(pkg) => {
+                    packages.insert(pkg.name.clone(), entry);
+                }
+                CatalogEntry::OpmChannel(chan) => {
+                    channels
+                        .entry(chan.package.clone())
+                        .or_default()
+                        .push(entry);
+                }
+                CatalogEntry::OlmBundle(bund) => {
+                    bundles.entry(bund.package.clone()).or_default().push(entry);
+                }
+                CatalogEntry::OlmDeprecations(deprecation) => {
+                    deprecations.insert(deprecation.package.clone(), entry);
+                }
+            }
+        }
+
+        Catalog {
+            packages,
+            channels,
+            bundles,
+            deprecations,
+            version_scheme: VersionScheme::default(),
+        }
+    }
+
+    /// Version of a bundle, or of a channel entry that may lack a bundle document
+    pub fn resolve_version(&self, package: &str, name: &str) -> ResolvedVersion {
+        resolve(self.bundle(package, name), name, self.version_scheme)
+    }
+
+    /// Shortcut for the resolved version itself
+    pub fn version_of(&self, package: &str, name: &str) -> Option<Version> {
+        self.resolve_version(package, name).version
+    }
+
+    /// Add the documents of another catalog, replacing the ones with the same
+    /// identity in place. Returns a description of every replaced document.
+    pub fn merge(&mut self, other: Catalog) -> Vec<String> {
+        let mut replaced = Vec::new();
+        for (name, entry) in other.packages {
+            if self.packages.insert(name.clone(), entry).is_some() {
+                replaced.push(format!("package {}", name));
+            }
+        }
+        for (name, entry) in other.deprecations {
+            if self.deprecations.insert(name.clone(), entry).is_some() {
+                replaced.push(format!("deprecations of {}", name));
+            }
+        }
+
+        let identity = |entry: &CatalogEntry| match entry {
+            CatalogEntry::OpmChannel(channel) => {
+                format!("channel {}/{}", channel.package, channel.name)
+            }
+            CatalogEntry::OlmBundle(bundle) => format!("bundle {}/{}", bundle.package, bundle.name),
+            _ => String::new(),
+        };
+        for (maps, other_map) in [
+            (&mut self.channels, other.channels),
+            (&mut self.bundles, other.bundles),
+        ] {
+            for (package, entries) in other_map {
+                let existing = maps.entry(package).or_default();
+                for entry in entries {
+                    let id = identity(&entry);
+                    match existing.iter_mut().find(|current| identity(current) == id) {
+                        Some(current) => {
+                            *current = entry;
+                            replaced.push(id);
+                        }
+                        None => existing.push(entry),
+                    }
+                }
+            }
+        }
+        replaced
+    }
+
+    /// Names of every package with any document in the catalog, sorted
+    pub fn package_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .packages
+            .keys()
+            .chain(self.channels.keys())
+            .chain(self.bundles.keys())
+            .chain(self.deprecations.keys())
+            .map(String::as_str)
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Drop the documents of every package not listed
+    pub fn retain_packages(&mut self, packages: &[String]) {
+        let keep = |package: &String| packages.contains(package);
+        self.packages.retain(|package, _| keep(package));
+        self.channels.retain(|package, _| keep(package));
+        self.bundles.retain(|package, _| keep(package));
+        self.deprecations.retain(|package, _| keep(package));
+    }
+
+    pub fn channel(&self, package: &str, name: &str) -> Option<&Channel> {
+        self.channels_of(package)
+            .find(|channel| channel.name == name)
+    }
+
+    pub fn bundle(&self, package: &str, name: &str) -> Option<&Bundle> {
+        self.bundles_of(package).find(|bundle| bundle.name == name)
+    }
+
+    pub fn deprecations_of(&self, package: &str) -> Option<&Deprecations> {
+        match self.deprecations.get(package) {
+            Some(CatalogEntry::OlmDeprecations(deprecations)) => Some(deprecations),
+            _ => None,
+        }
+    }
+
+    /// Channels of a single package
+    pub fn channels_of<'a>(&'a self, package: &str) -> impl Iterator<Item = &'a Channel> {
+        self.channels
+            .get(package)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| match entry {
+                CatalogEntry::OpmChannel(channel) => Some(channel),
+                _ => None,
+            })
+    }
+
+    /// Bundles of a single package
+    pub fn bundles_of<'a>(&'a self, package: &str) -> impl Iterator<Item = &'a Bundle> {
+        self.bundles
+            .get(package)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| match entry {
+                CatalogEntry::OlmBundle(bundle) => Some(bundle),
+                _ => None,
+            })
+    }
+
+    /// Channels of every package
+    pub fn all_channels(&self) -> impl Iterator<Item = &Channel> {
+        self.channels
+            .values()
+            .flatten()
+            .filter_map(|entry| match entry {
+                CatalogEntry::OpmChannel(channel) => Some(channel),
+                _ => None,
+            })
+    }
+
+    /// Bundles of every package
+    pub fn all_bundles(&self) -> impl Iterator<Item = &Bundle> {
+        self.bundles
+            .values()
+            .flatten()
+            .filter_map(|entry| match entry {
+                CatalogEntry::OlmBundle(bundle) => Some(bundle),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(entries: &str) -> Channel {
+        serde_yaml::from_str(&format!("name: stable\npackage: m\nentries:\n{}", entries))
+            .expect("valid channel")
+    }
+
+    fn head_names(channel: &Channel) -> Vec<&str> {
+        channel
+            .heads()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn heads_ignore_entries_replaced_or_skipped() {
+        let linear = channel("- name: m.v1\n- name: m.v2\n  replaces: m.v1\n- name: m.v3\n  replaces: m.v2\n  skips: [m.v4]\n- name: m.v4\n");
+        assert_eq!(head_names(&linear), ["m.v3"]);
+        assert_eq!(linear.head().map(|head| head.name.as_str()), Some("m.v3"));
+
+        // A skipRange covering an entry is no incoming edge
+        let ranged = channel("- name: m.v1.0.0\n- name: m.v2.0.0\n  skipRange: '<2.0.0'\n");
+        assert_eq!(head_names(&ranged), ["m.v1.0.0", "m.v2.0.0"]);
+        assert!(ranged.head().is_none());
+    }
+
+    #[test]
+    fn heads_leave_out_entries_pointing_at_themselves() {
+        let looped = channel("- name: m.v1\n  replaces: m.v1\n- name: m.v2\n  skips: [m.v2]\n");
+        assert!(head_names(&looped).is_empty());
+        assert!(looped.head().is_none());
+    }
+
+    #[test]
+    fn bundle_display_keeps_internal_property_fields_out() {
+        let bundle: Bundle = serde_yaml::from_str(
+            "name: m.v1.0.0\npackage: m\nimage: quay.io/x/m:1\nproperties:\n- type: olm.package\n  value:\n    packageName: m\n- type: olm.gvk\n  value:\n    group: g\n    kind: K\n    version: v1\n",
+        )
+        .expect("valid bundle");
+        let shown = bundle.to_string();
+        assert!(
+            shown.contains("\n    - olm.package: {\"packageName\":\"m\"} (malformed: "),
+            "{}",
+            shown
+        );
+        assert!(
+            shown.ends_with("\n    - olm.gvk: {\"group\":\"g\",\"kind\":\"K\",\"version\":\"v1\"}"),
+            "{}",
+            shown
+        );
+        assert!(
+            !shown.contains("raw") && !shown.contains("None"),
+            "{}",
+            shown
+        );
+    }
+}
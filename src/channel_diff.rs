@@ -0,0 +1,116 @@
+use std::fmt;
+
+use crate::catalog::{Channel, ChannelEntry};
+use crate::labels::label;
+
+/// Edges of an entry present on both sides that differ between them
+pub struct EntryChange {
+    pub name: String,
+    pub changes: Vec<(&'static str, String, String)>,
+}
+
+/// Differences between two versions of a channel, or two channels
+pub struct ChannelDiff {
+    pub left: String,
+    pub right: String,
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+    pub changed: Vec<EntryChange>,
+}
+
+impl ChannelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn edge_changes(left: &ChannelEntry, right: &ChannelEntry) -> Vec<(&'static str, String, String)> {
+    let mut changes = Vec::new();
+    if left.replaces != right.replaces {
+        changes.push(("replaces", left.replaces.clone(), right.replaces.clone()));
+    }
+    if left.skips != right.skips {
+        changes.push((
+            "skips",
+            format!("{:?}", left.skips),
+            format!("{:?}", right.skips),
+        ));
+    }
+    if left.skip_range != right.skip_range {
+        changes.push((
+            "skipRange",
+            left.skip_range.clone().unwrap_or_default(),
+            right.skip_range.clone().unwrap_or_default(),
+        ));
+    }
+    changes
+}
+
+/// Compare the entries and upgrade edges of two channels. `left` and `right`
+/// label the sides in the output.
+pub fn diff(left: &Channel, right: &Channel, left_label: &str, right_label: &str) -> ChannelDiff {
+    let only_left = left
+        .entries
+        .iter()
+        .filter(|entry| right.entry(&entry.name).is_none())
+        .map(|entry| entry.name.clone())
+        .collect();
+    let only_right = right
+        .entries
+        .iter()
+        .filter(|entry| left.entry(&entry.name).is_none())
+        .map(|entry| entry.name.clone())
+        .collect();
+    let changed = left
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let changes = edge_changes(entry, right.entry(&entry.name)?);
+            if changes.is_empty() {
+                return None;
+            }
+            Some(EntryChange {
+                name: entry.name.clone(),
+                changes,
+            })
+        })
+        .collect();
+
+    ChannelDiff {
+        left: left_label.to_string(),
+        right: right_label.to_string(),
+        only_left,
+        only_right,
+        changed,
+    }
+}
+
+impl fmt::Display for ChannelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Comparing {} with {}", self.left, self.right)?;
+        if self.is_empty() {
+            return write!(f, "\n  No differences");
+        }
+        for (label, entries) in [
+            (&self.left, &self.only_left),
+            (&self.right, &self.only_right),
+        ] {
+            if !entries.is_empty() {
+                write!(f, "\n  Only in {}:", label)?;
+                for entry in entries {
+                    write!(f, "\n    - {}", entry)?;
+                }
+            }
+        }
+        if !self.changed.is_empty() {
+            write!(f, "\n  {}:", label("changed_edges"))?;
+            for entry in &self.changed {
+                write!(f, "\n    - {}", entry.name)?;
+                for (edge, before, after) in &entry.changes {
+                    write!(f, "\n      {}: {} -> {}", edge, before, after)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+
+use crate::catalog::{Bundle, Catalog};
+use crate::gvk::{required_gvks, Gvk, GvkIndex};
+use crate::labels::label;
+use crate::version::VersionRange;
+
+/// Dependency declared by a bundle
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Requirement {
+    /// olm.package.required
+    Package {
+        name: String,
+        version_range: Option<String>,
+    },
+    /// olm.gvk.required
+    Gvk(Gvk),
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Requirement::Package {
+                name,
+                version_range: Some(range),
+            } => write!(f, "package {} {}", name, range),
+            Requirement::Package { name, .. } => write!(f, "package {}", name),
+            Requirement::Gvk(gvk) => write!(f, "api {}", gvk),
+        }
+    }
+}
+
+fn requirements(bundle: &Bundle) -> Vec<Requirement> {
+    let mut requirements: Vec<Requirement> = bundle
+        .properties
+        .iter()
+        .filter(|property| property.property_type == "olm.package.required")
+        .filter_map(|property| {
+            let field = |name: &str| {
+                property
+                    .value
+                    .get(name)
+                    .and_then(|value| value.as_str())
+                    .map(String::from)
+            };
+            Some(Requirement::Package {
+                name: field("packageName")?,
+                version_range: field("versionRange"),
+            })
+        })
+        .collect();
+    requirements.extend(required_gvks(bundle).into_iter().map(Requirement::Gvk));
+    requirements
+}
+
+/// Package of the closure with the requirement chain that pulled it in
+#[derive(Debug)]
+pub struct Included {
+    pub package: String,
+    /// Packages from the requested one down to this one
+    pub chain: Vec<String>,
+    /// Requirement of the previous package in the chain, None for requested packages
+    pub reason: Option<Requirement>,
+}
+
+/// Requirement nothing in the catalog satisfies
+#[derive(Debug)]
+pub struct Unsatisfied {
+    pub chain: Vec<String>,
+    pub requirement: Requirement,
+}
+
+#[derive(Debug, Default)]
+pub struct Closure {
+    pub packages: Vec<Included>,
+    /// Bundle and related images of every included package
+    pub images: BTreeSet<String>,
+    pub unsatisfied: Vec<Unsatisfied>,
+    /// Packages left out because they are deeper than --max-depth
+    pub depth_limited: BTreeSet<String>,
+}
+
+/// Packages providing a requirement, empty when it can't be satisfied
+fn providers(
+    requirement: &Requirement,
+    catalog: &Catalog,
+    index: &GvkIndex,
+) -> Result<BTreeSet<String>, String> {
+    match requirement {
+        Requirement::Package {
+            name,
+            version_range,
+        } => {
+            let range = version_range
+                .as_deref()
+                .map(VersionRange::parse)
+                .transpose()?;
+            let satisfied = catalog.bundles_of(name).any(|bundle| {
+                range.as_ref().is_none_or(|range| {
+                    catalog
+                        .version_of(name, &bundle.name)
+                        .is_some_and(|version| range.matches(&version))
+                })
+            });
+            Ok(if satisfied {
+                BTreeSet::from([name.clone()])
+            } else {
+                BTreeSet::new()
+            })
+        }
+        Requirement::Gvk(gvk) => Ok(index
+            .providers
+            .get(gvk)
+            .into_iter()
+            .flatten()
+            .map(|provider| provider.package.clone())
+            .collect()),
+    }
+}
+
+/// Walk the requirements of the given packages breadth first, every provider
+/// of a requirement is pulled in since any of them may get picked at install time
+pub fn closure(
+    catalog: &Catalog,
+    roots: &[String],
+    max_depth: Option<usize>,
+) -> Result<Closure, String> {
+    let index = GvkIndex::build(catalog);
+    let mut closure = Closure::default();
+    let mut included: HashMap<String, usize> = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for root in roots {
+        if !catalog.packages.contains_key(root) {
+            return Err(format!("package '{}' not found in the catalog", root));
+        }
+        if included.contains_key(root) {
+            continue;
+        }
+        included.insert(root.clone(), closure.packages.len());
+        closure.packages.push(Included {
+            package: root.clone(),
+            chain: vec![root.clone()],
+            reason: None,
+        });
+        queue.push_back((root.clone(), 0));
+    }
+
+    while let Some((package, depth)) = queue.pop_front() {
+        let chain = closure.packages[included[&package]].chain.clone();
+        let requirements: BTreeSet<Requirement> = catalog
+            .bundles_of(&package)
+            .flat_map(requirements)
+            .collect();
+
+        for requirement in requirements {
+            let providers = providers(&requirement, catalog, &index).map_err(|err| {
+                format!("package '{}' requires {}: {}", package, requirement, err)
+            })?;
+            if providers.is_empty() {
+                closure.unsatisfied.push(Unsatisfied {
+                    chain: chain.clone(),
+                    requirement,
+                });
+                continue;
+            }
+            for provider in providers {
+                if included.contains_key(&provider) {
+                    continue;
+                }
+                if max_depth.is_some_and(|max| depth >= max) {
+                    closure.depth_limited.insert(provider);
+                    continue;
+                }
+                let mut provider_chain = chain.clone();
+                provider_chain.push(provider.clone());
+                included.insert(provider.clone(), closure.packages.len());
+                closure.packages.push(Included {
+                    package: provider.clone(),
+                    chain: provider_chain,
+                    reason: Some(requirement.clone()),
+                });
+                queue.push_back((provider, depth + 1));
+            }
+        }
+    }
+
+    for package in &closure.packages {
+        for bundle in catalog.bundles_of(&package.package) {
+            closure.images.insert(bundle.image.clone());
+            closure.images.extend(
+                bundle
+                    .related_images
+                    .iter()
+                    .map(|related| related.image.clone()),
+            );
+        }
+    }
+    closure
+        .depth_limited
+        .retain(|package| !included.contains_key(package));
+
+    Ok(closure)
+}
+
+impl fmt::Display for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.unsatisfied.is_empty() {
+            writeln!(f, "{}:", label("unsatisfiable_requirements"))?;
+            for unsatisfied in &self.unsatisfied {
+                writeln!(
+                    f,
+                    "- {} requires {}: nothing in the catalog provides it",
+                    unsatisfied.chain.join(" -> "),
+                    unsatisfied.requirement
+                )?;
+            }
+        }
+
+        writeln!(f, "{}:", label("packages"))?;
+        for package in &self.packages {
+            match &package.reason {
+                Some(reason) => writeln!(
+                    f,
+                    "- {}: {} ({})",
+                    package.package,
+                    package.chain.join(" -> "),
+                    reason
+                )?,
+                None => writeln!(f, "- {} (requested)", package.package)?,
+            }
+        }
+
+        if !self.depth_limited.is_empty() {
+            writeln!(f, "{}:", label("not_followed"))?;
+            for package in &self.depth_limited {
+                writeln!(f, "- {}", package)?;
+            }
+        }
+
+        write!(f, "{}:", label("images"))?;
+        for image in &self.images {
+            write!(f, "\n- {}", image)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// User settings from `$XDG_CONFIG_HOME/catalog-browser/config.yaml`, or the
+/// file `CATALOG_BROWSER_CONFIG` points to
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    /// Subcommand and arguments run when only --file is given, e.g. `list packages`
+    pub default_command: Option<String>,
+    /// `false` never pipes output through the pager, like --no-pager
+    pub pager: Option<bool>,
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("CATALOG_BROWSER_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("catalog-browser").join("config.yaml"))
+}
+
+impl Config {
+    /// Load the config file, a missing file is the default configuration
+    pub fn load() -> Result<Config, String> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_yaml::from_str::<Option<Config>>(&content)
+                .map(Option::unwrap_or_default)
+                .map_err(|err| format!("Invalid config file {}: {}", path.display(), err)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(format!("Failed to read {}: {}", path.display(), err)),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+/// Unified diff of two texts, for dry runs previewing file changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Lines of context around each change
+const CONTEXT: usize = 3;
+/// Past this many edits the middle is shown as replaced wholesale rather
+/// than searched for the smallest diff
+const MAX_EDITS: usize = 2000;
+
+/// Myers' diff of the lines, as operations in order
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![Op::Equal; prefix];
+    ops.extend(middle_script(a, b));
+    ops.extend(vec![Op::Equal; suffix]);
+    ops
+}
+
+fn middle_script(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let offset = n + m + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    let mut trace = Vec::new();
+
+    let mut found = false;
+    for d in 0..=(n + m).min(MAX_EDITS as isize) {
+        // Backtracking only looks at the diagonals reached with d - 1 edits
+        let low = (offset - d - 1).max(0) as usize;
+        let high = (offset + d + 1) as usize;
+        trace.push((low, v[low..=high].to_vec()));
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                found = true;
+                break;
+            }
+        }
+        if found {
+            break;
+        }
+    }
+    if !found {
+        let mut ops = vec![Op::Delete; a.len()];
+        ops.extend(vec![Op::Insert; b.len()]);
+        return ops;
+    }
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, (low, v)) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let at = |k: isize| v[(k + offset) as usize - low];
+        let previous_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = at(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            ops.push(Op::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            ops.push(if x == previous_x {
+                Op::Insert
+            } else {
+                Op::Delete
+            });
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    ops.reverse();
+    ops
+}
+
+/// `git diff` style rendering, empty when the texts are the same
+pub fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = edit_script(&old_lines, &new_lines);
+    let changes: Vec<usize> = (0..ops.len()).filter(|&i| ops[i] != Op::Equal).collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Line numbers on both sides before each operation
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut old_line, mut new_line) = (0, 0);
+    for op in &ops {
+        positions.push((old_line, new_line));
+        match op {
+            Op::Equal => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Op::Delete => old_line += 1,
+            Op::Insert => new_line += 1,
+        }
+    }
+    positions.push((old_line, new_line));
+
+    let mut output = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut index = 0;
+    while index < changes.len() {
+        let start = changes[index].saturating_sub(CONTEXT);
+        let mut last = changes[index];
+        while index + 1 < changes.len() && changes[index + 1] - last <= 2 * CONTEXT {
+            index += 1;
+            last = changes[index];
+        }
+        let end = (last + 1 + CONTEXT).min(ops.len());
+        index += 1;
+
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        let range = |start: usize, count: usize| -> String {
+            match count {
+                0 => format!("{},0", start),
+                1 => format!("{}", start + 1),
+                _ => format!("{},{}", start + 1, count),
+            }
+        };
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_end - old_start),
+            range(new_start, new_end - new_start)
+        ));
+        for (op, &(old_line, new_line)) in ops[start..end].iter().zip(&positions[start..end]) {
+            let (mark, line) = match op {
+                Op::Equal => (' ', old_lines[old_line]),
+                Op::Delete => ('-', old_lines[old_line]),
+                Op::Insert => ('+', new_lines[new_line]),
+            };
+            output.push(mark);
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output
+}
@@ -0,0 +1,75 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::catalog::{Bundle, Catalog, CatalogEntry, Channel, Deprecations, Package};
+use crate::loader::{FileSummaries, SkippedDocument};
+
+/// Output formats of `dump`
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum DumpFormat {
+    Json,
+}
+
+/// Bundle along with the version the tool resolved for it
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct DumpBundle<'a> {
+    #[serde(flatten)]
+    bundle: &'a Bundle,
+    resolved_version: Option<String>,
+    version_source: &'static str,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DumpSkipped<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    document: &'a SkippedDocument,
+}
+
+/// Everything the loader understood, and everything it left out
+#[derive(Serialize, JsonSchema)]
+pub struct Dump<'a> {
+    packages: Vec<&'a Package>,
+    channels: Vec<&'a Channel>,
+    bundles: Vec<DumpBundle<'a>>,
+    deprecations: Vec<&'a Deprecations>,
+    skipped: Vec<DumpSkipped<'a>>,
+}
+
+pub fn dump<'a>(catalog: &'a Catalog, files: &'a FileSummaries) -> Dump<'a> {
+    let mut dump = Dump {
+        packages: Vec::new(),
+        channels: Vec::new(),
+        bundles: Vec::new(),
+        deprecations: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for name in catalog.package_names() {
+        if let Some(CatalogEntry::OlmPackage(package)) = catalog.packages.get(name) {
+            dump.packages.push(package);
+        }
+        dump.channels.extend(catalog.channels_of(name));
+        for bundle in catalog.bundles_of(name) {
+            let resolved = catalog.resolve_version(&bundle.package, &bundle.name);
+            dump.bundles.push(DumpBundle {
+                bundle,
+                resolved_version: resolved.version.map(|version| version.to_string()),
+                version_source: resolved.source.as_str(),
+            });
+        }
+        dump.deprecations.extend(catalog.deprecations_of(name));
+    }
+
+    for (file, summary) in files {
+        dump.skipped.extend(
+            summary
+                .skipped
+                .iter()
+                .map(|document| DumpSkipped { file, document }),
+        );
+    }
+
+    dump
+}
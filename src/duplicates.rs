@@ -0,0 +1,108 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::catalog::Catalog;
+use crate::image::ImageReference;
+
+/// Packages whose bundle images largely overlap, likely the same operator
+/// published under two names
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatePair {
+    pub packages: [String; 2],
+    /// Bundle images of each package
+    pub images: [usize; 2],
+    /// Shared images over the images of the smaller package, in percent
+    pub overlap: u8,
+    pub shared: Vec<String>,
+}
+
+impl DuplicatePair {
+    /// Package whose every image the other one has, the one to drop
+    pub fn covered(&self) -> Option<&str> {
+        (0..2)
+            .find(|&side| self.images[side] == self.shared.len())
+            .map(|side| self.packages[side].as_str())
+    }
+}
+
+/// Digest when the reference is pinned, so tags and mirrors of one image
+/// compare equal, the full reference otherwise
+fn fingerprint(image: &str) -> String {
+    ImageReference::parse(image)
+        .digest
+        .unwrap_or_else(|| image.to_string())
+}
+
+pub fn duplicates(catalog: &Catalog, threshold: u8) -> Vec<DuplicatePair> {
+    // Fingerprints of each package with the reference first seen for each
+    let mut packages: BTreeMap<&str, BTreeMap<String, &str>> = BTreeMap::new();
+    for bundle in catalog.all_bundles() {
+        packages
+            .entry(&bundle.package)
+            .or_default()
+            .entry(fingerprint(&bundle.image))
+            .or_insert(&bundle.image);
+    }
+
+    let packages: Vec<_> = packages.into_iter().collect();
+    let mut pairs = Vec::new();
+    for (index, (left, left_images)) in packages.iter().enumerate() {
+        for (right, right_images) in &packages[index + 1..] {
+            let shared: BTreeSet<&str> = left_images
+                .iter()
+                .filter(|(fingerprint, _)| right_images.contains_key(*fingerprint))
+                .map(|(_, &image)| image)
+                .collect();
+            let smaller = left_images.len().min(right_images.len());
+            if shared.is_empty() || smaller == 0 {
+                continue;
+            }
+            let overlap = (shared.len() * 100 / smaller) as u8;
+            if overlap < threshold {
+                continue;
+            }
+            pairs.push(DuplicatePair {
+                packages: [left.to_string(), right.to_string()],
+                images: [left_images.len(), right_images.len()],
+                overlap,
+                shared: shared.into_iter().map(String::from).collect(),
+            });
+        }
+    }
+    pairs.sort_by(|a, b| b.overlap.cmp(&a.overlap).then(a.packages.cmp(&b.packages)));
+    pairs
+}
+
+impl fmt::Display for DuplicatePair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} and {}: {}% overlap, {} shared images",
+            self.packages[0],
+            self.packages[1],
+            self.overlap,
+            self.shared.len()
+        )?;
+        for side in 0..2 {
+            write!(
+                f,
+                "\n  {}: {} images, {} not shared",
+                self.packages[side],
+                self.images[side],
+                self.images[side] - self.shared.len()
+            )?;
+        }
+        if self.images == [self.shared.len(); 2] {
+            write!(f, "\n  both packages have the same images")?;
+        } else if let Some(covered) = self.covered() {
+            write!(f, "\n  every image of {} is in the other package", covered)?;
+        }
+        for image in &self.shared {
+            write!(f, "\n  - {}", image)?;
+        }
+        Ok(())
+    }
+}
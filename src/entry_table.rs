@@ -0,0 +1,75 @@
+use crate::catalog::{Catalog, Channel};
+
+/// Columns of the channel entry table
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum Column {
+    Channel,
+    Name,
+    /// Version from the resolution rules of --version-scheme
+    Version,
+    Replaces,
+    /// Number of skipped bundles
+    Skips,
+    SkipRange,
+    /// Whether an olm.deprecations entry covers the bundle
+    Deprecated,
+    /// Whether the catalog has an olm.bundle document for the entry
+    Bundle,
+}
+
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Channel,
+    Column::Name,
+    Column::Version,
+    Column::Replaces,
+    Column::Skips,
+    Column::SkipRange,
+    Column::Deprecated,
+    Column::Bundle,
+];
+
+impl Column {
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Channel => "channel",
+            Column::Name => "name",
+            Column::Version => "version",
+            Column::Replaces => "replaces",
+            Column::Skips => "skips",
+            Column::SkipRange => "skip-range",
+            Column::Deprecated => "deprecated",
+            Column::Bundle => "bundle",
+        }
+    }
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+/// One row per entry of the channels, with the requested columns
+pub fn rows(channels: &[&Channel], columns: &[Column], catalog: &Catalog) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    for channel in channels {
+        let deprecations = catalog.deprecations_of(&channel.package);
+        for entry in &channel.entries {
+            let cell = |column: &Column| match column {
+                Column::Channel => channel.name.clone(),
+                Column::Name => entry.name.clone(),
+                Column::Version => catalog
+                    .version_of(&channel.package, &entry.name)
+                    .map(|version| version.to_string())
+                    .unwrap_or_default(),
+                Column::Replaces => entry.replaces.clone(),
+                Column::Skips => entry.skips.len().to_string(),
+                Column::SkipRange => entry.skip_range.clone().unwrap_or_default(),
+                Column::Deprecated => yes_no(deprecations.is_some_and(|deprecations| {
+                    deprecations.bundle_message(&entry.name).is_some()
+                })),
+                Column::Bundle => yes_no(catalog.bundle(&channel.package, &entry.name).is_some()),
+            };
+            rows.push(columns.iter().map(cell).collect());
+        }
+    }
+    rows
+}
@@ -0,0 +1,103 @@
+/// Invocation answering a common question. Commands use the file names of
+/// the selftest catalogs, which runs every one of them, so they keep working.
+pub struct Example {
+    pub question: &'static str,
+    /// Arguments after the program name, split on whitespace
+    pub args: &'static str,
+    /// Text the output has when run against the selftest catalogs
+    pub expect: &'static str,
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        question: "Which packages does the catalog have?",
+        args: "-f catalog.yaml list packages",
+        expect: "- demo-base",
+    },
+    Example {
+        question: "Which channels contain bundle demo.v1.2.0?",
+        args: "-f catalog.yaml show channel demo --output table --columns channel,name",
+        expect: "fast     demo.v1.2.0",
+    },
+    Example {
+        question: "In which order does OLM walk the channel?",
+        args: "-f catalog.yaml show channel demo --ordered",
+        expect: "- demo.v1.2.0",
+    },
+    Example {
+        question: "How does a cluster on demo.v1.0.0 get to demo.v1.2.0?",
+        args: "-f catalog.yaml path --package demo --channel stable --from demo.v1.0.0 --to demo.v1.2.0",
+        expect: "1 hops",
+    },
+    Example {
+        question: "Why is demo.v1.2.0 offered, or not, to a cluster on demo.v1.0.0?",
+        args: "-f catalog.yaml explain --package demo --channel stable --bundle demo.v1.2.0 --installed demo.v1.0.0",
+        expect: "installable",
+    },
+    Example {
+        question: "Which images have to be mirrored for package demo, its dependencies included?",
+        args: "-f catalog.yaml closure --package demo",
+        expect: "quay.io/example/demo-base-bundle@sha256:",
+    },
+    Example {
+        question: "What changed in a channel between two indexes?",
+        args: "-f previous=previous.yaml -f current=catalog.yaml channel-diff --package demo --channel stable --between previous,current",
+        expect: "Only in demo/stable (current):",
+    },
+    Example {
+        question: "Does the catalog have common mistakes?",
+        args: "-f catalog.yaml validate",
+        expect: "0 errors",
+    },
+    Example {
+        question: "What would copying package demo into its own catalog write?",
+        args: "-f catalog.yaml extract --package demo --out demo.yaml --dry-run",
+        expect: "1 packages would be written to demo.yaml",
+    },
+];
+
+impl Example {
+    /// The subcommand the example runs, after the global options
+    pub fn subcommand(&self) -> &'static str {
+        let mut args = self.args.split_whitespace();
+        while let Some(arg) = args.next() {
+            if arg == "-f" {
+                args.next();
+            } else if !arg.starts_with('-') {
+                return arg;
+            }
+        }
+        ""
+    }
+
+    pub fn command_line(&self) -> String {
+        format!("catalog-cli {}", self.args)
+    }
+}
+
+/// Examples of one subcommand for the end of its help, None when it has none
+pub fn after_help(subcommand: &str) -> Option<String> {
+    let mut text = String::new();
+    for example in EXAMPLES
+        .iter()
+        .filter(|example| example.subcommand() == subcommand)
+    {
+        text.push_str(&format!(
+            "\n  # {}\n  {}\n",
+            example.question,
+            example.command_line()
+        ));
+    }
+    (!text.is_empty()).then(|| format!("Examples:{}", text.trim_end()))
+}
+
+/// Every example, for the `examples` subcommand
+pub fn print() {
+    for (index, example) in EXAMPLES.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("{}", example.question);
+        println!("  {}", example.command_line());
+    }
+}
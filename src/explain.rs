@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use crate::catalog::{Catalog, Channel};
+use crate::version::parse_major_minor;
+
+pub struct ExplainRequest<'a> {
+    pub package: &'a str,
+    pub channel: &'a str,
+    pub bundle: &'a str,
+    pub installed: Option<&'a str>,
+    pub ocp_version: Option<&'a str>,
+}
+
+/// Outcome of the checks, the bundle is installable when there are no reasons
+#[derive(Debug, Default)]
+pub struct Verdict {
+    /// Everything that stops OLM from offering the bundle
+    pub reasons: Vec<String>,
+    /// Observations that don't block the installation on their own
+    pub notes: Vec<String>,
+}
+
+impl Verdict {
+    pub fn installable(&self) -> bool {
+        self.reasons.is_empty()
+    }
+}
+
+/// Walk the upgrade edges of the channel starting at the installed bundle
+fn reachable(catalog: &Catalog, channel: &Channel, installed: &str, target: &str) -> bool {
+    let mut visited = HashSet::new();
+    let mut frontier = vec![installed.to_string()];
+    while let Some(current) = frontier.pop() {
+        if current == target {
+            return true;
+        }
+        if !visited.insert(current.clone()) {
+            continue;
+        }
+        let version = catalog.version_of(&channel.package, &current);
+        for entry in &channel.entries {
+            if entry.upgrades_from(&current, version.as_ref()) {
+                frontier.push(entry.name.clone());
+            }
+        }
+    }
+    false
+}
+
+pub fn explain(request: &ExplainRequest, catalog: &Catalog) -> Result<Verdict, String> {
+    let mut verdict = Verdict::default();
+    let ocp_version = request
+        .ocp_version
+        .map(|version| {
+            parse_major_minor(version)
+                .ok_or_else(|| format!("invalid OpenShift version '{}'", version))
+        })
+        .transpose()?;
+
+    if !catalog.packages.contains_key(request.package) {
+        verdict.reasons.push(format!(
+            "package '{}' is not in the catalog",
+            request.package
+        ));
+        return Ok(verdict);
+    }
+    let Some(channel) = catalog.channel(request.package, request.channel) else {
+        verdict.reasons.push(format!(
+            "channel '{}' doesn't exist in package '{}'",
+            request.channel, request.package
+        ));
+        return Ok(verdict);
+    };
+
+    if channel.entry(request.bundle).is_none() {
+        let others: Vec<&str> = catalog
+            .channels_of(request.package)
+            .filter(|other| other.entry(request.bundle).is_some())
+            .map(|other| other.name.as_str())
+            .collect();
+        verdict.reasons.push(if others.is_empty() {
+            format!(
+                "'{}' is not an entry of any channel of package '{}'",
+                request.bundle, request.package
+            )
+        } else {
+            format!(
+                "'{}' is not an entry of channel '{}', it is only in: {}",
+                request.bundle,
+                request.channel,
+                others.join(", ")
+            )
+        });
+    } else if let Some(installed) = request.installed {
+        if installed == request.bundle {
+            verdict
+                .notes
+                .push(format!("'{}' is the installed version", installed));
+        } else if !reachable(catalog, channel, installed, request.bundle) {
+            verdict.reasons.push(format!(
+                "'{}' is not reachable from the installed '{}' through the replaces, skips or skipRange edges of channel '{}'",
+                request.bundle, installed, request.channel
+            ));
+        }
+    }
+
+    match catalog.bundle(request.package, request.bundle) {
+        None => verdict.reasons.push(format!(
+            "there is no olm.bundle document named '{}' in package '{}'",
+            request.bundle, request.package
+        )),
+        Some(bundle) => match (bundle.max_openshift_version(), ocp_version) {
+            (Some(max), Some(ocp)) => match parse_major_minor(&max) {
+                Some(max_version) if ocp > max_version => verdict.reasons.push(format!(
+                    "its maxOpenShiftVersion {} blocks it on OpenShift {}",
+                    max,
+                    request.ocp_version.unwrap_or_default()
+                )),
+                Some(_) => {}
+                None => verdict
+                    .notes
+                    .push(format!("unable to parse maxOpenShiftVersion '{}'", max)),
+            },
+            (Some(max), None) => verdict.notes.push(format!(
+                "maxOpenShiftVersion is {}, pass --ocp-version to check it",
+                max
+            )),
+            (None, _) => {}
+        },
+    }
+
+    if let Some(deprecations) = catalog.deprecations_of(request.package) {
+        if let Some(message) = deprecations.package_message() {
+            verdict.reasons.push(format!(
+                "package '{}' is deprecated: {}",
+                request.package, message
+            ));
+        }
+        if let Some(message) = deprecations.channel_message(request.channel) {
+            verdict.reasons.push(format!(
+                "channel '{}' is deprecated: {}",
+                request.channel, message
+            ));
+        }
+        if let Some(message) = deprecations.bundle_message(request.bundle) {
+            verdict.reasons.push(format!(
+                "bundle '{}' is deprecated: {}",
+                request.bundle, message
+            ));
+        }
+    }
+
+    Ok(verdict)
+}
@@ -0,0 +1,85 @@
+use crate::catalog::Catalog;
+use crate::output::csv_row;
+
+/// Output formats of the upgrade graph
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    /// `package,channel,from,to,edge_type` rows, one per upgrade edge
+    EdgesCsv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EdgeType {
+    Replaces,
+    Skips,
+    /// Pseudo-edge, `to` holds the range instead of a bundle name
+    SkipRange,
+}
+
+impl EdgeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeType::Replaces => "replaces",
+            EdgeType::Skips => "skips",
+            EdgeType::SkipRange => "skiprange",
+        }
+    }
+}
+
+/// Upgrade edge of a channel, from the older bundle to the one replacing or skipping it
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edge {
+    pub package: String,
+    pub channel: String,
+    pub from: String,
+    pub to: String,
+    pub edge_type: EdgeType,
+}
+
+/// Every edge of the catalog, optionally limited to some packages and/or a
+/// channel, sorted so the output is stable between runs
+pub fn edges(catalog: &Catalog, packages: &[String], channel: Option<&str>) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    let channels = catalog
+        .all_channels()
+        .filter(|c| packages.is_empty() || packages.contains(&c.package))
+        .filter(|c| channel.is_none_or(|channel| c.name == channel));
+
+    for c in channels {
+        let edge = |from: &str, to: &str, edge_type| Edge {
+            package: c.package.clone(),
+            channel: c.name.clone(),
+            from: from.to_string(),
+            to: to.to_string(),
+            edge_type,
+        };
+        for entry in &c.entries {
+            if !entry.replaces.is_empty() {
+                edges.push(edge(&entry.replaces, &entry.name, EdgeType::Replaces));
+            }
+            for skip in &entry.skips {
+                edges.push(edge(skip, &entry.name, EdgeType::Skips));
+            }
+            if let Some(range) = &entry.skip_range {
+                edges.push(edge(&entry.name, range, EdgeType::SkipRange));
+            }
+        }
+    }
+
+    edges.sort();
+    edges
+}
+
+pub fn edges_csv(edges: &[Edge]) -> String {
+    let mut lines = vec![csv_row(&["package", "channel", "from", "to", "edge_type"])];
+    for edge in edges {
+        lines.push(csv_row(&[
+            edge.package.as_str(),
+            &edge.channel,
+            &edge.from,
+            &edge.to,
+            edge.edge_type.as_str(),
+        ]));
+    }
+    lines.join("\n")
+}
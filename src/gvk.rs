@@ -0,0 +1,250 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+use crate::catalog::{Bundle, Catalog, CatalogEntry};
+use crate::validate::{Finding, Severity};
+
+/// Group/version/kind of an API provided by a bundle
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, JsonSchema)]
+pub struct Gvk {
+    pub group: String,
+    pub version: String,
+    pub kind: String,
+}
+
+impl fmt::Display for Gvk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}, Kind={}", self.group, self.version, self.kind)
+    }
+}
+
+/// GVKs from the olm.gvk properties of a bundle
+pub fn provided_gvks(bundle: &Bundle) -> Vec<Gvk> {
+    gvks_of(bundle, "olm.gvk")
+}
+
+/// GVKs from the olm.gvk.required properties of a bundle
+pub fn required_gvks(bundle: &Bundle) -> Vec<Gvk> {
+    gvks_of(bundle, "olm.gvk.required")
+}
+
+fn gvks_of(bundle: &Bundle, property_type: &str) -> Vec<Gvk> {
+    bundle
+        .properties
+        .iter()
+        .filter(|property| property.property_type == property_type)
+        .filter_map(|property| {
+            let field = |name: &str| {
+                property
+                    .value
+                    .get(name)
+                    .and_then(|value| value.as_str())
+                    .map(String::from)
+            };
+            Some(Gvk {
+                group: field("group")?,
+                version: field("version")?,
+                kind: field("kind")?,
+            })
+        })
+        .collect()
+}
+
+/// Bundle providing a GVK
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Provider {
+    pub package: String,
+    pub bundle: String,
+    pub version: Option<String>,
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{} ({})", self.bundle, version),
+            None => write!(f, "{}", self.bundle),
+        }
+    }
+}
+
+/// Every GVK of the catalog with the bundles providing it
+pub struct GvkIndex {
+    pub providers: BTreeMap<Gvk, Vec<Provider>>,
+}
+
+impl GvkIndex {
+    pub fn build(catalog: &Catalog) -> GvkIndex {
+        let mut providers: BTreeMap<Gvk, Vec<Provider>> = BTreeMap::new();
+        for bundle in catalog.all_bundles() {
+            for gvk in provided_gvks(bundle) {
+                providers.entry(gvk).or_default().push(Provider {
+                    package: bundle.package.clone(),
+                    bundle: bundle.name.clone(),
+                    version: bundle.version().map(|version| version.to_string()),
+                });
+            }
+        }
+        for list in providers.values_mut() {
+            list.sort_by(|a, b| (&a.package, &a.bundle).cmp(&(&b.package, &b.bundle)));
+        }
+        GvkIndex { providers }
+    }
+}
+
+/// GVK provided by a candidate that a different package already owns
+pub struct Conflict {
+    pub gvk: Gvk,
+    pub candidate: Vec<Provider>,
+    pub existing: Vec<Provider>,
+}
+
+/// Compare the GVKs of the candidate bundles against the index, ignoring
+/// providers from the candidate's own packages
+pub fn find_conflicts(candidate: &[&Bundle], index: &GvkIndex) -> Vec<Conflict> {
+    let mut candidate_gvks: BTreeMap<Gvk, Vec<Provider>> = BTreeMap::new();
+    for bundle in candidate {
+        for gvk in provided_gvks(bundle) {
+            candidate_gvks.entry(gvk).or_default().push(Provider {
+                package: bundle.package.clone(),
+                bundle: bundle.name.clone(),
+                version: bundle.version().map(|version| version.to_string()),
+            });
+        }
+    }
+
+    candidate_gvks
+        .into_iter()
+        .filter_map(|(gvk, candidate)| {
+            let existing: Vec<Provider> = index
+                .providers
+                .get(&gvk)?
+                .iter()
+                .filter(|provider| !candidate.iter().any(|own| own.package == provider.package))
+                .cloned()
+                .collect();
+            if existing.is_empty() {
+                return None;
+            }
+            Some(Conflict {
+                gvk,
+                candidate,
+                existing,
+            })
+        })
+        .collect()
+}
+
+// Heads of every package's default channel, the bundles installed by default
+fn default_heads(catalog: &Catalog) -> HashSet<(String, String)> {
+    let mut heads = HashSet::new();
+    for entry in catalog.packages.values() {
+        let CatalogEntry::OlmPackage(package) = entry else {
+            continue;
+        };
+        let Some(channel) = package
+            .default_channel
+            .as_deref()
+            .and_then(|name| catalog.channel(&package.name, name))
+        else {
+            continue;
+        };
+        for head in channel.heads() {
+            heads.insert((package.name.clone(), head.name.clone()));
+        }
+    }
+    heads
+}
+
+/// Every GVK ownership overlap of the catalog, tiered by how likely it is
+/// to break installs: different versions of a group and kind and several
+/// bundles of one package are expected, several packages are suspicious and
+/// several packages whose default channel heads provide it clash by default
+pub fn conflict_report(catalog: &Catalog, index: &GvkIndex) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let mut versions: BTreeMap<(&str, &str), BTreeSet<&str>> = BTreeMap::new();
+    for gvk in index.providers.keys() {
+        versions
+            .entry((&gvk.group, &gvk.kind))
+            .or_default()
+            .insert(&gvk.version);
+    }
+    for ((group, kind), versions) in versions {
+        if versions.len() > 1 {
+            findings.push(Finding {
+                severity: Severity::Info,
+                rule: "gvk-versions",
+                location: format!("{}, Kind={}", group, kind),
+                message: format!(
+                    "served at versions {}",
+                    versions.into_iter().collect::<Vec<_>>().join(", ")
+                ),
+            });
+        }
+    }
+
+    let heads = default_heads(catalog);
+    for (gvk, providers) in &index.providers {
+        let mut packages: BTreeMap<&str, Vec<&Provider>> = BTreeMap::new();
+        for provider in providers {
+            packages
+                .entry(&provider.package)
+                .or_default()
+                .push(provider);
+        }
+
+        if packages.len() == 1 {
+            if providers.len() > 1 {
+                findings.push(Finding {
+                    severity: Severity::Info,
+                    rule: "gvk-package-versions",
+                    location: gvk.to_string(),
+                    message: format!(
+                        "provided by {} bundles of package {}",
+                        providers.len(),
+                        providers[0].package
+                    ),
+                });
+            }
+            continue;
+        }
+
+        let by_default: Vec<&str> = packages
+            .iter()
+            .filter(|(_, providers)| {
+                providers
+                    .iter()
+                    .any(|p| heads.contains(&(p.package.clone(), p.bundle.clone())))
+            })
+            .map(|(package, _)| *package)
+            .collect();
+        let listed = packages.keys().copied().collect::<Vec<_>>().join(", ");
+        if by_default.len() > 1 {
+            findings.push(Finding {
+                severity: Severity::Error,
+                rule: "gvk-default-heads",
+                location: gvk.to_string(),
+                message: format!(
+                    "provided by the default channel heads of packages {}",
+                    by_default.join(", ")
+                ),
+            });
+        } else {
+            findings.push(Finding {
+                severity: Severity::Warning,
+                rule: "gvk-multiple-packages",
+                location: gvk.to_string(),
+                message: format!("provided by packages {}", listed),
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.location.cmp(&b.location))
+    });
+    findings
+}
@@ -0,0 +1,159 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt;
+
+use crate::catalog::{Catalog, CatalogEntry};
+use crate::id::ObjectId;
+
+/// Traffic-light state of a check, ordered from best to worst
+#[derive(
+    clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Cheap checks of one package, `validate` has the details
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageHealth {
+    pub id: String,
+    pub package: String,
+    /// The package names a default channel that exists
+    pub default_channel: Status,
+    /// Every channel has exactly one head
+    pub heads: Status,
+    /// Replaces targets are in the package, warn for pruned ones, fail for
+    /// ones belonging to another package
+    pub replaces: Status,
+    /// Every bundle is in a channel
+    pub orphans: Status,
+    /// Nothing of the package is deprecated
+    pub deprecations: Status,
+    /// Worst of the checks
+    pub status: Status,
+}
+
+fn check(ok: bool, otherwise: Status) -> Status {
+    if ok {
+        Status::Ok
+    } else {
+        otherwise
+    }
+}
+
+fn package_health(catalog: &Catalog, package: &str) -> PackageHealth {
+    let default_channel = match catalog.packages.get(package) {
+        Some(CatalogEntry::OlmPackage(document)) => document
+            .default_channel
+            .as_deref()
+            .is_some_and(|name| catalog.channel(package, name).is_some()),
+        _ => false,
+    };
+
+    let heads = catalog
+        .channels_of(package)
+        .all(|channel| channel.heads().len() == 1);
+
+    let mut replaces = Status::Ok;
+    for channel in catalog.channels_of(package) {
+        for entry in &channel.entries {
+            if entry.replaces.is_empty()
+                || channel.entry(&entry.replaces).is_some()
+                || catalog.bundle(package, &entry.replaces).is_some()
+            {
+                continue;
+            }
+            let elsewhere = catalog
+                .all_bundles()
+                .any(|bundle| bundle.name == entry.replaces && bundle.package != package);
+            replaces = replaces.max(if elsewhere {
+                Status::Fail
+            } else {
+                Status::Warn
+            });
+        }
+    }
+
+    let orphans = catalog.bundles_of(package).all(|bundle| {
+        catalog
+            .channels_of(package)
+            .any(|channel| channel.entry(&bundle.name).is_some())
+    });
+
+    let deprecations = catalog
+        .deprecations_of(package)
+        .is_none_or(|deprecations| deprecations.entries.is_empty());
+
+    let mut health = PackageHealth {
+        id: ObjectId::package(package).to_string(),
+        package: package.to_string(),
+        default_channel: check(default_channel, Status::Fail),
+        heads: check(heads, Status::Fail),
+        replaces,
+        orphans: check(orphans, Status::Warn),
+        deprecations: check(deprecations, Status::Warn),
+        status: Status::Ok,
+    };
+    health.status = [
+        health.default_channel,
+        health.heads,
+        health.replaces,
+        health.orphans,
+        health.deprecations,
+    ]
+    .into_iter()
+    .max()
+    .unwrap_or(Status::Ok);
+    health
+}
+
+/// Health of every package, sorted by name
+pub fn health(catalog: &Catalog) -> Vec<PackageHealth> {
+    catalog
+        .package_names()
+        .into_iter()
+        .map(|package| package_health(catalog, package))
+        .collect()
+}
+
+pub fn health_table(packages: &[&PackageHealth]) -> Vec<Vec<String>> {
+    packages
+        .iter()
+        .map(|health| {
+            vec![
+                health.package.clone(),
+                health.default_channel.to_string(),
+                health.heads.to_string(),
+                health.replaces.to_string(),
+                health.orphans.to_string(),
+                health.deprecations.to_string(),
+                health.status.to_string(),
+            ]
+        })
+        .collect()
+}
+
+pub const HEALTH_HEADERS: [&str; 7] = [
+    "package",
+    "default channel",
+    "heads",
+    "replaces",
+    "orphans",
+    "deprecations",
+    "status",
+];
@@ -0,0 +1,270 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::catalog::Catalog;
+
+/// Stable identifier of a catalog object, independent of the file it came from:
+///
+/// ```text
+/// id      = package [ "/" ( channel [ "/" entry ] | bundle ) ]
+/// package = "pkg:" name
+/// channel = "channel:" name
+/// entry   = "entry:" name
+/// bundle  = "bundle:" name
+/// ```
+///
+/// `%` and `/` in names are written `%25` and `%2F`, e.g.
+/// `pkg:etcd/channel:stable/entry:etcd.v0.9.2` or `pkg:etcd/bundle:etcd.v0.9.2`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ObjectId {
+    Package(String),
+    Channel {
+        package: String,
+        name: String,
+    },
+    /// Entry of a channel, named after the bundle it stands for
+    Entry {
+        package: String,
+        channel: String,
+        name: String,
+    },
+    Bundle {
+        package: String,
+        name: String,
+    },
+}
+
+fn encode(name: &str) -> String {
+    name.replace('%', "%25").replace('/', "%2F")
+}
+
+fn decode(encoded: &str) -> Result<String, String> {
+    let mut name = String::new();
+    let mut rest = encoded;
+    while let Some(index) = rest.find('%') {
+        name.push_str(&rest[..index]);
+        match rest.get(index..index + 3) {
+            Some("%25") => name.push('%'),
+            Some(escape) if escape.eq_ignore_ascii_case("%2F") => name.push('/'),
+            _ => return Err(format!("invalid escape in '{}', only %25 and %2F", encoded)),
+        }
+        rest = &rest[index + 3..];
+    }
+    name.push_str(rest);
+    if name.is_empty() {
+        return Err("empty name".to_string());
+    }
+    Ok(name)
+}
+
+impl ObjectId {
+    pub fn package(package: &str) -> ObjectId {
+        ObjectId::Package(package.to_string())
+    }
+
+    pub fn channel(package: &str, name: &str) -> ObjectId {
+        ObjectId::Channel {
+            package: package.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn entry(package: &str, channel: &str, name: &str) -> ObjectId {
+        ObjectId::Entry {
+            package: package.to_string(),
+            channel: channel.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn bundle(package: &str, name: &str) -> ObjectId {
+        ObjectId::Bundle {
+            package: package.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    /// Parse the text form, the inverse of Display
+    pub fn parse(id: &str) -> Result<ObjectId, String> {
+        let invalid = |reason: &str| format!("invalid identifier '{}': {}", id, reason);
+        let mut segments = id.split('/');
+        let segment = |segment: Option<&str>, prefix: &str| -> Result<String, String> {
+            let value = segment
+                .and_then(|segment| segment.strip_prefix(prefix))
+                .ok_or_else(|| invalid(&format!("expected '{}'", prefix)))?;
+            decode(value).map_err(|err| invalid(&err))
+        };
+
+        let package = segment(segments.next(), "pkg:")?;
+        let parsed = match segments.next() {
+            None => ObjectId::Package(package),
+            Some(next) if next.starts_with("bundle:") => ObjectId::Bundle {
+                package,
+                name: segment(Some(next), "bundle:")?,
+            },
+            Some(next) => {
+                let channel = segment(Some(next), "channel:")?;
+                match segments.next() {
+                    None => ObjectId::Channel {
+                        package,
+                        name: channel,
+                    },
+                    Some(entry) => ObjectId::Entry {
+                        package,
+                        channel,
+                        name: segment(Some(entry), "entry:")?,
+                    },
+                }
+            }
+        };
+        if segments.next().is_some() {
+            return Err(invalid("unexpected segment after the object"));
+        }
+        Ok(parsed)
+    }
+}
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectId::Package(package) => write!(f, "pkg:{}", encode(package)),
+            ObjectId::Channel { package, name } => {
+                write!(f, "pkg:{}/channel:{}", encode(package), encode(name))
+            }
+            ObjectId::Entry {
+                package,
+                channel,
+                name,
+            } => write!(
+                f,
+                "pkg:{}/channel:{}/entry:{}",
+                encode(package),
+                encode(channel),
+                encode(name)
+            ),
+            ObjectId::Bundle { package, name } => {
+                write!(f, "pkg:{}/bundle:{}", encode(package), encode(name))
+            }
+        }
+    }
+}
+
+/// Catalog object in JSON output, with its identifier in front
+#[derive(Serialize, JsonSchema)]
+pub struct Identified<'a, T> {
+    /// Stable identifier, e.g. `pkg:etcd/bundle:etcd.v0.9.2`
+    pub id: String,
+    #[serde(flatten)]
+    pub item: &'a T,
+}
+
+impl<'a, T> Identified<'a, T> {
+    pub fn new(id: ObjectId, item: &'a T) -> Identified<'a, T> {
+        Identified {
+            id: id.to_string(),
+            item,
+        }
+    }
+}
+
+/// Identifiers more than one object of the catalog would get, with how many
+/// objects share each
+pub fn ambiguities(catalog: &Catalog) -> Vec<(ObjectId, usize)> {
+    let mut counts: BTreeMap<ObjectId, usize> = BTreeMap::new();
+    for channel in catalog.all_channels() {
+        *counts
+            .entry(ObjectId::channel(&channel.package, &channel.name))
+            .or_default() += 1;
+        for entry in &channel.entries {
+            *counts
+                .entry(ObjectId::entry(
+                    &channel.package,
+                    &channel.name,
+                    &entry.name,
+                ))
+                .or_default() += 1;
+        }
+    }
+    for bundle in catalog.all_bundles() {
+        *counts
+            .entry(ObjectId::bundle(&bundle.package, &bundle.name))
+            .or_default() += 1;
+    }
+    counts.into_iter().filter(|(_, count)| *count > 1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::CatalogEntry;
+
+    #[test]
+    fn parse_reads_every_form_of_the_grammar() {
+        assert_eq!(ObjectId::parse("pkg:etcd"), Ok(ObjectId::package("etcd")));
+        assert_eq!(
+            ObjectId::parse("pkg:etcd/channel:stable"),
+            Ok(ObjectId::channel("etcd", "stable"))
+        );
+        assert_eq!(
+            ObjectId::parse("pkg:etcd/channel:stable/entry:etcd.v0.9.2"),
+            Ok(ObjectId::entry("etcd", "stable", "etcd.v0.9.2"))
+        );
+        assert_eq!(
+            ObjectId::parse("pkg:etcd/bundle:etcd.v0.9.2"),
+            Ok(ObjectId::bundle("etcd", "etcd.v0.9.2"))
+        );
+    }
+
+    #[test]
+    fn escaped_names_round_trip() {
+        let id = ObjectId::entry("a/b", "100%", "c/%2F");
+        let text = id.to_string();
+        assert_eq!(text, "pkg:a%2Fb/channel:100%25/entry:c%2F%252F");
+        assert_eq!(ObjectId::parse(&text), Ok(id));
+        // Either case of the slash escape is accepted
+        assert_eq!(ObjectId::parse("pkg:a%2fb"), Ok(ObjectId::package("a/b")));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_identifiers() {
+        for (id, reason) in [
+            ("etcd", "expected 'pkg:'"),
+            ("pkg:", "empty name"),
+            ("pkg:etcd/", "expected 'channel:'"),
+            ("pkg:etcd/stable", "expected 'channel:'"),
+            ("pkg:etcd/channel:stable/etcd.v1", "expected 'entry:'"),
+            ("pkg:etcd/bundle:etcd.v1/entry:x", "unexpected segment"),
+            ("pkg:etcd/channel:a/entry:b/entry:c", "unexpected segment"),
+            ("pkg:50%", "invalid escape"),
+            ("pkg:a%20b", "invalid escape"),
+        ] {
+            let err = ObjectId::parse(id).expect_err(id);
+            assert!(
+                err.starts_with(&format!("invalid identifier '{}': ", id)) && err.contains(reason),
+                "{}: {}",
+                id,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn ambiguities_count_objects_sharing_an_identifier() {
+        let entries = [
+            "schema: olm.channel\nname: stable\npackage: etcd\nentries:\n- name: etcd.v1\n- name: etcd.v1\n",
+            "schema: olm.bundle\nname: etcd.v1\npackage: etcd\nimage: example.com/etcd:v1\n",
+            "schema: olm.bundle\nname: etcd.v1\npackage: etcd\nimage: example.com/etcd:v1\n",
+        ]
+        .map(|document| serde_yaml::from_str::<CatalogEntry>(document).unwrap());
+        let catalog = Catalog::from_entries(entries.into());
+        assert_eq!(
+            ambiguities(&catalog),
+            [
+                (ObjectId::entry("etcd", "stable", "etcd.v1"), 2),
+                (ObjectId::bundle("etcd", "etcd.v1"), 2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::catalog::{Catalog, CatalogEntry};
+
+/// How a labeled catalog differs from the first one
+pub struct Difference {
+    pub label: String,
+    /// Label of the first catalog, the one compared against
+    pub reference: String,
+    /// Objects like `bundle etcd/etcd.v0.9.2` only in this catalog
+    pub extra: Vec<String>,
+    pub missing: Vec<String>,
+    /// Objects present in both with different content
+    pub changed: Vec<String>,
+}
+
+/// Every document of the catalog keyed by what it is, with its content
+fn objects(catalog: &Catalog) -> BTreeMap<String, serde_json::Value> {
+    let mut objects = BTreeMap::new();
+    let mut add = |key: String, entry: &CatalogEntry| {
+        objects.insert(key, serde_json::to_value(entry).unwrap_or_default());
+    };
+    for (name, entry) in &catalog.packages {
+        add(format!("package {}", name), entry);
+    }
+    for entry in catalog.channels.values().flatten() {
+        if let CatalogEntry::OpmChannel(channel) = entry {
+            add(
+                format!("channel {}/{}", channel.package, channel.name),
+                entry,
+            );
+        }
+    }
+    for entry in catalog.bundles.values().flatten() {
+        if let CatalogEntry::OlmBundle(bundle) = entry {
+            add(format!("bundle {}/{}", bundle.package, bundle.name), entry);
+        }
+    }
+    for (package, entry) in &catalog.deprecations {
+        add(format!("deprecations {}", package), entry);
+    }
+    objects
+}
+
+/// Compare every catalog with the first, only the ones that differ are returned
+pub fn differences(catalogs: &[(String, Catalog)]) -> Vec<Difference> {
+    let Some(((reference_label, first), rest)) = catalogs.split_first() else {
+        return Vec::new();
+    };
+    let reference = objects(first);
+    rest.iter()
+        .filter_map(|(label, catalog)| {
+            let other = objects(catalog);
+            let difference = Difference {
+                label: label.clone(),
+                reference: reference_label.clone(),
+                extra: other
+                    .keys()
+                    .filter(|key| !reference.contains_key(*key))
+                    .cloned()
+                    .collect(),
+                missing: reference
+                    .keys()
+                    .filter(|key| !other.contains_key(*key))
+                    .cloned()
+                    .collect(),
+                changed: other
+                    .iter()
+                    .filter(|(key, value)| reference.get(*key).is_some_and(|r| r != *value))
+                    .map(|(key, _)| key.clone())
+                    .collect(),
+            };
+            let same = difference.extra.is_empty()
+                && difference.missing.is_empty()
+                && difference.changed.is_empty();
+            (!same).then_some(difference)
+        })
+        .collect()
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} differs from {}:", self.label, self.reference)?;
+        for (what, objects) in [
+            ("only here", &self.extra),
+            ("missing", &self.missing),
+            ("different", &self.changed),
+        ] {
+            for object in objects {
+                write!(f, "\n  {} {}", what, object)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+/// Container image reference split into its name, tag and digest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference {
+    /// Registry and repository, e.g. `quay.io/operatorhubio/etcd`
+    pub name: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl ImageReference {
+    pub fn parse(reference: &str) -> ImageReference {
+        let (rest, digest) = match reference.split_once('@') {
+            Some((rest, digest)) => (rest, Some(digest.to_string())),
+            None => (reference, None),
+        };
+        // A colon before the last slash separates a registry port, not a tag
+        let last_slash = rest.rfind('/').map_or(0, |index| index + 1);
+        let (name, tag) = match rest[last_slash..].rfind(':') {
+            Some(index) => (
+                &rest[..last_slash + index],
+                Some(rest[last_slash + index + 1..].to_string()),
+            ),
+            None => (rest, None),
+        };
+        ImageReference {
+            name: name.to_string(),
+            tag,
+            digest,
+        }
+    }
+
+    pub fn is_digest_pinned(&self) -> bool {
+        self.digest.is_some()
+    }
+}
+
+/// What a strict reading of a reference finds wrong with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceProblem {
+    /// Not a valid reference at all
+    Invalid(String),
+    /// No registry, so it resolves to docker.io
+    ImplicitDockerHub,
+    /// Uppercase repository, only valid once lowercased
+    Uppercase,
+}
+
+const MAX_NAME_LENGTH: usize = 255;
+const MAX_TAG_LENGTH: usize = 128;
+
+fn is_domain_component(component: &str) -> bool {
+    let bytes = component.as_bytes();
+    !bytes.is_empty()
+        && bytes
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'-')
+        && bytes[0] != b'-'
+        && bytes[bytes.len() - 1] != b'-'
+}
+
+fn is_domain(domain: &str) -> bool {
+    // Split off a port, the colons of an IPv6 address are inside brackets
+    let host = match domain.rfind(':') {
+        Some(index) if !domain[index..].contains(']') => {
+            let port = &domain[index + 1..];
+            if port.is_empty() || !port.bytes().all(|b| b.is_ascii_digit()) {
+                return false;
+            }
+            &domain[..index]
+        }
+        _ => domain,
+    };
+    match host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+    {
+        Some(ipv6) => !ipv6.is_empty() && ipv6.chars().all(|c| c.is_ascii_hexdigit() || c == ':'),
+        None => host.split('.').all(is_domain_component),
+    }
+}
+
+/// Lowercase alphanumerics joined by `.`, `_`, `__` or runs of `-`
+fn is_path_component(component: &str) -> bool {
+    let bytes = component.as_bytes();
+    let alphanumeric = |b: &u8| b.is_ascii_lowercase() || b.is_ascii_digit();
+    let (Some(first), Some(last)) = (bytes.first(), bytes.last()) else {
+        return false;
+    };
+    if !alphanumeric(first) || !alphanumeric(last) {
+        return false;
+    }
+    let mut separator = String::new();
+    for &b in bytes {
+        if alphanumeric(&b) {
+            if !matches!(separator.as_str(), "" | "." | "_" | "__")
+                && !separator.bytes().all(|b| b == b'-')
+            {
+                return false;
+            }
+            separator.clear();
+        } else {
+            separator.push(b as char);
+        }
+    }
+    true
+}
+
+fn is_tag(tag: &str) -> bool {
+    let bytes = tag.as_bytes();
+    let word = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_';
+    !bytes.is_empty()
+        && bytes.len() <= MAX_TAG_LENGTH
+        && word(&bytes[0])
+        && bytes.iter().all(|b| word(b) || *b == b'.' || *b == b'-')
+}
+
+fn is_digest(digest: &str) -> bool {
+    let Some((algorithm, hex)) = digest.split_once(':') else {
+        return false;
+    };
+    let algorithm_ok = algorithm.split(['+', '.', '_', '-']).all(|part| {
+        !part.is_empty()
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+    });
+    let hex_ok = match algorithm {
+        "sha256" => hex.len() == 64,
+        "sha512" => hex.len() == 128,
+        _ => hex.len() >= 32,
+    } && hex
+        .bytes()
+        .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    algorithm_ok && hex_ok
+}
+
+impl ImageReference {
+    /// Check a reference against the OCI distribution grammar, on top of the
+    /// split parse() does. Empty when the reference is fine.
+    pub fn problems(reference: &str) -> Vec<ReferenceProblem> {
+        let invalid = |message: &str| vec![ReferenceProblem::Invalid(message.to_string())];
+        if reference.is_empty() {
+            return invalid("empty reference");
+        }
+        if reference.chars().any(char::is_whitespace) {
+            return invalid("contains whitespace");
+        }
+
+        let parsed = ImageReference::parse(reference);
+        if parsed.tag.as_deref().is_some_and(|tag| !is_tag(tag)) {
+            return invalid("invalid tag");
+        }
+        if parsed
+            .digest
+            .as_deref()
+            .is_some_and(|digest| !is_digest(digest))
+        {
+            return invalid("invalid digest");
+        }
+        if parsed.name.len() > MAX_NAME_LENGTH {
+            return invalid("name longer than 255 characters");
+        }
+
+        let mut problems = Vec::new();
+        // Like docker, the first component is a registry when it looks like a host
+        let (domain, path) = match parsed.name.split_once('/') {
+            Some((first, rest))
+                if first.contains(['.', ':'])
+                    || first == "localhost"
+                    || first.chars().any(|c| c.is_ascii_uppercase()) =>
+            {
+                (Some(first), rest)
+            }
+            _ => (None, parsed.name.as_str()),
+        };
+        match domain {
+            Some(domain) if !is_domain(domain) => return invalid("invalid registry"),
+            Some(_) => {}
+            None => problems.push(ReferenceProblem::ImplicitDockerHub),
+        }
+        if path.split('/').any(|component| component.is_empty()) {
+            return invalid("empty path component");
+        }
+        let lowercase = path.to_ascii_lowercase();
+        if !lowercase.split('/').all(is_path_component) {
+            return invalid("invalid repository name");
+        }
+        if lowercase != path {
+            problems.push(ReferenceProblem::Uppercase);
+        }
+        problems
+    }
+}
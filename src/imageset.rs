@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+use crate::catalog::{Catalog, Channel};
+use crate::version::{Version, VersionRange};
+use crate::warnings::{self, Warning};
+use crate::write;
+
+/// oc-mirror ImageSetConfiguration, limited to the operators section
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageSetConfiguration {
+    kind: &'static str,
+    api_version: &'static str,
+    mirror: Mirror,
+}
+
+#[derive(Debug, Serialize)]
+struct Mirror {
+    operators: Vec<Operator>,
+}
+
+#[derive(Debug, Serialize)]
+struct Operator {
+    catalog: String,
+    packages: Vec<IncludePackage>,
+}
+
+#[derive(Debug, Serialize)]
+struct IncludePackage {
+    name: String,
+    channels: Vec<IncludeChannel>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IncludeChannel {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_version: Option<String>,
+}
+
+pub struct ImagesetRequest<'a> {
+    pub package: &'a str,
+    pub channel: Option<&'a str>,
+    pub version_range: Option<&'a str>,
+    pub catalog_image: &'a str,
+    pub out: &'a str,
+    pub dry_run: bool,
+}
+
+/// Work out the min/max bounds of a channel: the lowest and highest bundle
+/// versions matching the range, or the channel head when no range is given
+fn channel_bounds(
+    channel: &Channel,
+    catalog: &Catalog,
+    range: Option<&VersionRange>,
+) -> Result<(Option<Version>, Option<Version>), String> {
+    let version_of = |name: &str| catalog.version_of(&channel.package, name);
+
+    match range {
+        Some(range) => {
+            let mut versions: Vec<Version> = channel
+                .entries
+                .iter()
+                .filter_map(|entry| version_of(&entry.name))
+                .filter(|version| range.matches(version))
+                .collect();
+            versions.sort();
+            if versions.is_empty() {
+                return Err(format!(
+                    "no bundle in channel '{}' of package '{}' matches version range '{}'",
+                    channel.name, channel.package, range
+                ));
+            }
+            Ok((versions.first().cloned(), versions.last().cloned()))
+        }
+        None => match channel.head().and_then(|head| version_of(&head.name)) {
+            Some(version) => Ok((Some(version.clone()), Some(version))),
+            None => {
+                warnings::warn(Warning::new(
+                    "channel-head-unresolved",
+                    format!(
+                        "Unable to resolve the head version of channel '{}', leaving it unbounded",
+                        channel.name
+                    ),
+                ));
+                Ok((None, None))
+            }
+        },
+    }
+}
+
+pub fn generate_imageset(
+    request: &ImagesetRequest,
+    catalog: &Catalog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !catalog.packages.contains_key(request.package) {
+        return Err(format!("package '{}' not found in the catalog", request.package).into());
+    }
+
+    let package_channels: Vec<&Channel> = catalog
+        .channels_of(request.package)
+        .filter(|channel| request.channel.is_none_or(|name| channel.name == name))
+        .collect();
+
+    if package_channels.is_empty() {
+        return Err(match request.channel {
+            Some(name) => format!(
+                "channel '{}' not found in package '{}'",
+                name, request.package
+            ),
+            None => format!("package '{}' has no channels", request.package),
+        }
+        .into());
+    }
+
+    let range = request.version_range.map(VersionRange::parse).transpose()?;
+
+    let mut include_channels = Vec::new();
+    for channel in package_channels {
+        let (min_version, max_version) = channel_bounds(channel, catalog, range.as_ref())?;
+        include_channels.push(IncludeChannel {
+            name: channel.name.clone(),
+            min_version: min_version.map(|v| v.to_string()),
+            max_version: max_version.map(|v| v.to_string()),
+        });
+    }
+
+    let config = ImageSetConfiguration {
+        kind: "ImageSetConfiguration",
+        api_version: "mirror.openshift.io/v1alpha2",
+        mirror: Mirror {
+            operators: vec![Operator {
+                catalog: request.catalog_image.to_string(),
+                packages: vec![IncludePackage {
+                    name: request.package.to_string(),
+                    channels: include_channels,
+                }],
+            }],
+        },
+    };
+
+    write::write_output(
+        request.out,
+        &serde_yaml::to_string(&config)?,
+        request.dry_run,
+    )?;
+    if request.dry_run {
+        println!("ImageSetConfiguration would be written to {}", request.out);
+    } else {
+        println!("ImageSetConfiguration written to {}", request.out);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+/// Every label of the text output with its English text. `column.*` ones
+/// head table columns, keyed by the English header with spaces and dashes
+/// turned into underscores. JSON and CSV output never goes through these.
+const ENGLISH: &[(&str, &str)] = &[
+    ("additional_fields", "Additional fields"),
+    ("bundle", "Bundle"),
+    ("bundles", "Bundles"),
+    ("changed_edges", "Changed edges"),
+    ("channel", "Channel"),
+    ("channels", "Channels"),
+    ("created", "Created"),
+    ("default_channel", "Default channel"),
+    ("deprecated", "Deprecated"),
+    ("description", "Description"),
+    ("entries", "Entries"),
+    ("icon", "Icon"),
+    ("image", "Image"),
+    ("images", "Images"),
+    ("malformed", "malformed"),
+    ("not_followed", "Not followed, deeper than --max-depth"),
+    ("notes", "Notes"),
+    ("off_chain_entries", "Off-chain entries"),
+    ("package", "Package"),
+    ("packages", "Packages"),
+    ("properties", "Properties"),
+    ("recommended", "Recommended"),
+    ("related_images", "Related images"),
+    ("replaces", "replaces"),
+    ("resolved_version", "Resolved version"),
+    ("skip_range", "skip_range"),
+    ("skips", "skips"),
+    ("unsatisfiable_requirements", "Unsatisfiable requirements"),
+    ("column.annotation", "annotation"),
+    ("column.bundle", "bundle"),
+    ("column.bundles", "bundles"),
+    ("column.channel", "channel"),
+    ("column.channels", "channels"),
+    ("column.created", "created"),
+    ("column.default_channel", "default channel"),
+    ("column.deprecated", "deprecated"),
+    ("column.deprecations", "deprecations"),
+    ("column.failed", "failed"),
+    ("column.file", "file"),
+    ("column.heads", "heads"),
+    ("column.name", "name"),
+    ("column.newest", "newest"),
+    ("column.oldest", "oldest"),
+    ("column.openshift", "openshift"),
+    ("column.orphans", "orphans"),
+    ("column.other_properties", "other properties"),
+    ("column.package", "package"),
+    ("column.package_names", "package names"),
+    ("column.packages", "packages"),
+    ("column.replaces", "replaces"),
+    ("column.size", "size"),
+    ("column.skip_range", "skip-range"),
+    ("column.skips", "skips"),
+    ("column.status", "status"),
+    ("column.unknown", "unknown"),
+    ("column.version", "version"),
+];
+
+static LABELS: OnceLock<HashMap<&'static str, String>> = OnceLock::new();
+
+fn english(key: &str) -> Option<(&'static str, &'static str)> {
+    ENGLISH
+        .iter()
+        .find(|(english_key, _)| *english_key == key)
+        .copied()
+}
+
+fn lookup(key: &str) -> Option<&'static str> {
+    let (key, text) = english(key)?;
+    Some(
+        LABELS
+            .get()
+            .and_then(|labels| labels.get(key))
+            .map_or(text, String::as_str),
+    )
+}
+
+/// Replace labels with the ones of a `key=value` file, blank lines and
+/// lines starting with `#` are skipped. Labels the file doesn't set stay
+/// English, unknown keys are an error so typos don't go unnoticed.
+pub fn load(path: &str) -> Result<(), String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    let mut labels = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid =
+            |reason: String| format!("Invalid label at {}:{}: {}", path, index + 1, reason);
+        let Some((key, text)) = line.split_once('=') else {
+            return Err(invalid("expected key=value".to_string()));
+        };
+        let Some((key, _)) = english(key.trim()) else {
+            return Err(invalid(format!("unknown label '{}'", key.trim())));
+        };
+        labels.insert(key, text.trim().to_string());
+    }
+    let _ = LABELS.set(labels);
+    Ok(())
+}
+
+/// Text of a label, `key` is one of the labels listed above
+pub fn label(key: &'static str) -> &'static str {
+    lookup(key).unwrap_or(key)
+}
+
+/// Table column header, headers without a `column.*` label such as
+/// annotation keys are kept as they are
+pub fn header(header: &str) -> &str {
+    lookup(&format!("column.{}", header.replace([' ', '-'], "_"))).unwrap_or(header)
+}
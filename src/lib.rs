@@ -0,0 +1,79 @@
+//! Loading and querying of OLM file-based catalogs, the library behind
+//! `catalog-cli`. Embedders read catalogs with [`loader::CatalogReader`] or
+//! [`loader::load_path`] and query them through [`catalog::Catalog`]. The
+//! hidden modules are the commands of the binary and aren't a stable API.
+
+#[doc(hidden)]
+pub mod advice;
+#[doc(hidden)]
+pub mod annotations;
+#[doc(hidden)]
+pub mod archive;
+#[doc(hidden)]
+pub mod batch;
+pub mod catalog;
+#[doc(hidden)]
+pub mod channel_diff;
+#[doc(hidden)]
+pub mod closure;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod diff;
+#[doc(hidden)]
+pub mod dump;
+#[doc(hidden)]
+pub mod duplicates;
+#[doc(hidden)]
+pub mod entry_table;
+#[doc(hidden)]
+pub mod examples;
+#[doc(hidden)]
+pub mod explain;
+#[doc(hidden)]
+pub mod graph;
+#[doc(hidden)]
+pub mod gvk;
+#[doc(hidden)]
+pub mod health;
+#[doc(hidden)]
+pub mod id;
+#[doc(hidden)]
+pub mod identical;
+#[doc(hidden)]
+pub mod image;
+#[doc(hidden)]
+pub mod imageset;
+#[doc(hidden)]
+pub mod labels;
+pub mod loader;
+#[doc(hidden)]
+pub mod manifest;
+#[doc(hidden)]
+pub mod names;
+#[doc(hidden)]
+pub mod ocp;
+#[doc(hidden)]
+pub mod opm;
+#[doc(hidden)]
+pub mod output;
+#[doc(hidden)]
+pub mod pager;
+pub mod resolve;
+#[doc(hidden)]
+pub mod select;
+#[doc(hidden)]
+pub mod selftest;
+#[doc(hidden)]
+pub mod stats;
+pub mod template;
+#[doc(hidden)]
+pub mod timestamp;
+#[doc(hidden)]
+pub mod upgrade_path;
+#[doc(hidden)]
+pub mod validate;
+pub mod version;
+pub mod warnings;
+#[doc(hidden)]
+pub mod write;
@@ -0,0 +1,1808 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::archive;
+use crate::catalog::{Bundle, Catalog, CatalogEntry};
+use crate::manifest::{self, Manifest};
+use crate::names;
+use crate::opm;
+use crate::template::{first_placeholder, PlaceholderPolicy, Substitutions};
+use crate::warnings::{self, Warning};
+
+/// Optional upper bound, `unlimited` on the command line
+#[derive(Debug, Clone, Copy)]
+pub struct Limit(pub Option<u64>);
+
+impl FromStr for Limit {
+    type Err = String;
+
+    /// Accepts plain numbers, `unlimited` and binary size suffixes (`64MiB`, `1G`)
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if input.eq_ignore_ascii_case("unlimited") {
+            return Ok(Limit(None));
+        }
+        let split = input
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len());
+        let (number, suffix) = input.split_at(split);
+        let multiplier: u64 = match suffix.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kib" => 1 << 10,
+            "m" | "mib" => 1 << 20,
+            "g" | "gib" => 1 << 30,
+            _ => return Err(format!("invalid limit '{}'", input)),
+        };
+        number
+            .parse::<u64>()
+            .ok()
+            .and_then(|number| number.checked_mul(multiplier))
+            .map(|value| Limit(Some(value)))
+            .ok_or_else(|| format!("invalid limit '{}'", input))
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(value) => write!(f, "{}", value),
+            None => write!(f, "unlimited"),
+        }
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [(&str, u64); 3] = [("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)];
+    for (unit, size) in UNITS {
+        if bytes >= size && bytes.is_multiple_of(size) {
+            return format!("{} {}", bytes / size, unit);
+        }
+    }
+    format!("{} bytes", bytes)
+}
+
+pub struct LoadLimits {
+    pub max_document_size: Limit,
+    pub max_documents: Limit,
+    /// Nodes a document using aliases may expand to
+    pub max_alias_expansion: Limit,
+    /// Placeholder values applied to every document before parsing
+    pub substitutions: Substitutions,
+    /// Strip whitespace around and control characters in name fields
+    pub trim_names: bool,
+    /// Keep as little as possible, for --low-memory: no raw text of skipped
+    /// documents and no per-bundle sizes
+    pub low_memory: bool,
+    /// Render the image of grpc CatalogSources with opm and load the result
+    pub follow_image: bool,
+    /// Only parse documents of these schemas and drop the others unparsed,
+    /// None for all of them
+    pub schemas: Option<&'static [&'static str]>,
+}
+
+/// The command line defaults
+impl Default for LoadLimits {
+    fn default() -> LoadLimits {
+        LoadLimits {
+            max_document_size: Limit(Some(64 << 20)),
+            max_documents: Limit(None),
+            max_alias_expansion: Limit(Some(100_000)),
+            substitutions: Substitutions::default(),
+            trim_names: false,
+            follow_image: false,
+            low_memory: false,
+            schemas: None,
+        }
+    }
+}
+
+/// Single YAML document cut out of the input stream
+struct RawDocument {
+    /// Line the document starts on, 1-based
+    line: usize,
+    /// Size of the document in bytes
+    size: u64,
+    /// Document bytes, None when it was over the size limit and got discarded
+    content: Option<Vec<u8>>,
+}
+
+/// Position within a JSON object being read
+#[derive(Default)]
+struct JsonScan {
+    depth: u32,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonScan {
+    /// Length of `bytes` up to and including the one closing the object,
+    /// None when it goes on past them
+    fn feed(&mut self, bytes: &[u8]) -> Option<usize> {
+        for (index, &byte) in bytes.iter().enumerate() {
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        return Some(index + 1);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+fn newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&byte| byte == b'\n').count()
+}
+
+/// Splits a YAML stream on document markers without holding more than one
+/// document, and never more than `max_size` bytes of it, in memory. opm
+/// JSON streams have no document markers, each of their objects is a
+/// document, cut out as it is read with the same limit.
+struct DocumentReader<R> {
+    reader: R,
+    max_size: Option<u64>,
+    line: usize,
+    // Content that followed the `---` marker which started the next document
+    pending: Option<(usize, Vec<u8>)>,
+    /// Reading JSON objects rather than YAML documents
+    json: bool,
+    /// Rest of an oversized JSON object to read past
+    skipping: Option<JsonScan>,
+    done: bool,
+}
+
+impl<R: BufRead> DocumentReader<R> {
+    fn new(mut reader: R, max_size: Option<u64>) -> Self {
+        let json = reader.fill_buf().is_ok_and(|start| {
+            start
+                .iter()
+                .find(|byte| !byte.is_ascii_whitespace())
+                .is_some_and(|&byte| byte == b'{')
+        });
+        DocumentReader {
+            reader,
+            max_size,
+            line: 0,
+            pending: Some((1, Vec::new())),
+            json,
+            skipping: None,
+            done: false,
+        }
+    }
+
+    /// Read on through the JSON object `scan` is in, into `content` while it
+    /// fits the size limit. True once the object or the stream ended, false
+    /// when it went over the limit and the rest is still to be read.
+    fn read_object(
+        &mut self,
+        scan: &mut JsonScan,
+        content: &mut Option<Vec<u8>>,
+        size: &mut u64,
+    ) -> io::Result<bool> {
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(true);
+            }
+            let end = scan.feed(available);
+            let length = end.unwrap_or(available.len());
+            self.line += newlines(&available[..length]);
+            *size += length as u64;
+            if self.max_size.is_some_and(|max| *size > max) {
+                *content = None;
+            }
+            if let Some(content) = content {
+                content.extend_from_slice(&available[..length]);
+            }
+            self.reader.consume(length);
+            if end.is_some() {
+                return Ok(true);
+            }
+            if content.is_none() {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Next object of a JSON stream. None at the end of the stream, or
+    /// when what follows isn't an object and gets read as YAML instead.
+    fn next_object(&mut self) -> io::Result<Option<RawDocument>> {
+        if let Some(mut scan) = self.skipping.take() {
+            while !self.read_object(&mut scan, &mut None, &mut 0)? {}
+        }
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                self.done = true;
+                return Ok(None);
+            }
+            let blank = available
+                .iter()
+                .take_while(|byte| byte.is_ascii_whitespace())
+                .count();
+            let rest = blank == available.len();
+            self.line += newlines(&available[..blank]);
+            self.reader.consume(blank);
+            if !rest {
+                break;
+            }
+        }
+        if self.reader.fill_buf()?[0] != b'{' {
+            self.json = false;
+            self.pending = Some((self.line + 1, Vec::new()));
+            return Ok(None);
+        }
+
+        let line = self.line + 1;
+        let (mut scan, mut content, mut size) = (JsonScan::default(), Some(Vec::new()), 0);
+        // Oversized objects are reported right away, not once read through
+        if !self.read_object(&mut scan, &mut content, &mut size)? {
+            self.skipping = Some(scan);
+        }
+        Ok(Some(RawDocument {
+            line,
+            size,
+            content,
+        }))
+    }
+
+    /// Read one line, keeping at most `keep` bytes of it in `buf`.
+    /// Returns the full length of the line.
+    fn read_line(&mut self, buf: &mut Vec<u8>, keep: u64) -> io::Result<u64> {
+        let mut total = 0u64;
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(total);
+            }
+            let (chunk, found_newline) = match available.iter().position(|&b| b == b'\n') {
+                Some(index) => (&available[..=index], true),
+                None => (available, false),
+            };
+            let room = keep.saturating_sub(total) as usize;
+            buf.extend_from_slice(&chunk[..room.min(chunk.len())]);
+            let length = chunk.len();
+            total += length as u64;
+            self.reader.consume(length);
+            if found_newline {
+                return Ok(total);
+            }
+        }
+    }
+}
+
+fn is_document_marker(line: &[u8]) -> Option<&[u8]> {
+    for marker in [b"---", b"..."] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            if rest.is_empty() || rest[0].is_ascii_whitespace() {
+                // Content after `...` doesn't belong to any document
+                return Some(if marker == b"..." { &[] } else { rest });
+            }
+        }
+    }
+    None
+}
+
+fn is_blank(content: &[u8]) -> bool {
+    String::from_utf8_lossy(content).lines().all(|line| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with('#') || line.starts_with('%')
+    })
+}
+
+impl<R: BufRead> Iterator for DocumentReader<R> {
+    type Item = io::Result<RawDocument>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.json && !self.done {
+            match self.next_object() {
+                Ok(Some(document)) => return Some(Ok(document)),
+                Ok(None) => {}
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        while !self.done {
+            let (start, mut content) = self.pending.take()?;
+            let mut size = content.len() as u64;
+            let mut oversized = false;
+
+            loop {
+                // Keep enough of every line to recognize document markers
+                let keep = match (self.max_size, oversized) {
+                    (_, true) => 4,
+                    (Some(max), false) => max.saturating_sub(size) + 4,
+                    (None, false) => u64::MAX,
+                };
+                let mut line = Vec::new();
+                let length = match self.read_line(&mut line, keep) {
+                    Ok(length) => length,
+                    Err(err) => {
+                        self.done = true;
+                        return Some(Err(err));
+                    }
+                };
+                if length == 0 {
+                    self.done = true;
+                    break;
+                }
+                self.line += 1;
+
+                if let Some(rest) = is_document_marker(&line) {
+                    self.pending = Some((self.line, rest.to_vec()));
+                    break;
+                }
+
+                size += length;
+                if self.max_size.is_some_and(|max| size > max) {
+                    oversized = true;
+                    content = Vec::new();
+                } else if !oversized {
+                    content.extend_from_slice(&line);
+                }
+            }
+
+            if oversized {
+                return Some(Ok(RawDocument {
+                    line: start,
+                    size,
+                    content: None,
+                }));
+            }
+            if !is_blank(&content) {
+                return Some(Ok(RawDocument {
+                    line: start,
+                    size,
+                    content: Some(content),
+                }));
+            }
+        }
+        None
+    }
+}
+
+// Cheap check for alias references, false positives only cost the slower
+// alias-aware parse
+fn may_use_aliases(content: &str) -> bool {
+    content.lines().any(|line| {
+        line.char_indices().any(|(index, c)| {
+            c == '*'
+                && line[..index]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|prev| prev.is_whitespace() || "[{,:-".contains(prev))
+                && line[index + 1..]
+                    .chars()
+                    .next()
+                    .is_some_and(|next| next.is_alphanumeric() || next == '_')
+        })
+    })
+}
+
+fn count_nodes(value: &serde_yaml::Value) -> u64 {
+    use serde_yaml::Value;
+    1 + match value {
+        Value::Sequence(items) => items.iter().map(count_nodes).sum(),
+        Value::Mapping(mapping) => mapping
+            .iter()
+            .map(|(key, value)| count_nodes(key) + count_nodes(value))
+            .sum(),
+        Value::Tagged(tagged) => count_nodes(&tagged.value),
+        _ => 0,
+    }
+}
+
+const KNOWN_SCHEMAS: &[&str] = &[
+    "olm.package",
+    "olm.channel",
+    "olm.bundle",
+    "olm.deprecations",
+];
+
+/// Value of a top-level `key:` line of a block YAML document, None when
+/// there's no such line or the value takes a YAML parser to read, like
+/// quoted ones and the values of JSON documents
+fn top_level_value<'c>(content: &'c str, key: &str) -> Option<&'c str> {
+    let value = content.lines().find_map(|line| {
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .filter(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+    })?;
+    let value = value.trim();
+    let plain = !value.is_empty()
+        && !value.starts_with(['"', '\'', '&', '*', '!', '|', '>', '{', '['])
+        && !value.contains(" #");
+    plain.then_some(value)
+}
+
+/// Schema of a document without parsing all of it, from its top-level
+/// `schema:` line. Documents without one, like JSON ones, get parsed.
+fn peek_schema(content: &str) -> Option<String> {
+    match top_level_value(content, "schema") {
+        Some(value) => Some(value.to_string()),
+        None => serde_yaml::from_str::<SchemaOnly>(content).ok()?.schema,
+    }
+}
+
+#[derive(Deserialize)]
+struct SchemaOnly {
+    schema: Option<String>,
+}
+
+enum ParseError {
+    Invalid(String),
+    /// Valid document of a schema other than the OLM ones
+    UnknownSchema(String),
+    /// Expanding aliases went over the budget, the input is likely hostile
+    AliasLimit(String),
+}
+
+/// Deserialize a document, expanding anchors and aliases within the budget
+fn parse_document(
+    content: &str,
+    max_alias_expansion: Option<u64>,
+) -> Result<CatalogEntry, ParseError> {
+    // serde_yaml has its own guards against recursive and exponential aliases
+    let classify = |err: serde_yaml::Error| {
+        let message = err.to_string();
+        if message.starts_with("repetition limit exceeded")
+            || message.starts_with("recursion limit exceeded")
+        {
+            return ParseError::AliasLimit(message);
+        }
+        match serde_yaml::from_str::<SchemaOnly>(content) {
+            Ok(SchemaOnly {
+                schema: Some(schema),
+            }) if !KNOWN_SCHEMAS.contains(&schema.as_str()) => ParseError::UnknownSchema(schema),
+            _ => ParseError::Invalid(message),
+        }
+    };
+
+    if !may_use_aliases(content) {
+        return serde_yaml::from_str(content).map_err(classify);
+    }
+
+    let value: serde_yaml::Value = serde_yaml::from_str(content).map_err(classify)?;
+    let nodes = count_nodes(&value);
+    if let Some(max) = max_alias_expansion.filter(|&max| nodes > max) {
+        return Err(ParseError::AliasLimit(format!(
+            "expands to {} nodes, over --max-alias-expansion of {}",
+            nodes, max
+        )));
+    }
+    serde_yaml::from_value(value).map_err(|err| match serde_yaml::from_str::<SchemaOnly>(content) {
+        Ok(SchemaOnly {
+            schema: Some(schema),
+        }) if !KNOWN_SCHEMAS.contains(&schema.as_str()) => ParseError::UnknownSchema(schema),
+        _ => ParseError::Invalid(err.to_string()),
+    })
+}
+
+/// Document left out of the catalog, kept for `dump`
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedDocument {
+    pub line: usize,
+    /// `oversized`, `invalid` or `unknown-schema`
+    pub reason: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Raw document, None when it was over the size limit
+    pub content: Option<String>,
+}
+
+/// What happened while loading, reported when anything got skipped
+#[derive(Debug, Default)]
+pub struct LoadSummary {
+    pub documents: u64,
+    pub loaded: u64,
+    pub failed: u64,
+    pub oversized: u64,
+    /// Loading stopped early because of --max-documents
+    pub truncated: bool,
+    pub packages: u64,
+    pub channels: u64,
+    pub bundles: u64,
+    pub deprecations: u64,
+    /// Documents of schemas other than the OLM ones, skipped
+    pub unknown: u64,
+    /// Documents of schemas the command doesn't need, dropped unparsed
+    pub dropped: u64,
+    /// Source size of the loaded documents
+    pub loaded_bytes: u64,
+    /// Packages the loaded documents belong to
+    pub package_names: BTreeSet<String>,
+    pub skipped: Vec<SkippedDocument>,
+    pub bundle_sizes: Vec<BundleSize>,
+}
+
+/// Size of a bundle's source document and of the properties making it up
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSize {
+    pub package: String,
+    pub name: String,
+    pub bytes: u64,
+    pub properties: PropertySizes,
+}
+
+/// Serialized JSON size of the properties by type, which tracks the YAML
+/// source closely enough to tell where the bytes go
+#[derive(Debug, Default, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertySizes {
+    pub bundle_object: u64,
+    pub csv_metadata: u64,
+    pub other: u64,
+}
+
+impl PropertySizes {
+    fn of(bundle: &Bundle) -> PropertySizes {
+        let mut sizes = PropertySizes::default();
+        for property in &bundle.properties {
+            let bytes = serde_json::to_string(&property.value).map_or(0, |json| json.len() as u64);
+            match property.property_type.as_str() {
+                "olm.bundle.object" => sizes.bundle_object += bytes,
+                "olm.csv.metadata" => sizes.csv_metadata += bytes,
+                _ => sizes.other += bytes,
+            }
+        }
+        sizes
+    }
+}
+
+/// Load summary of every source file, by path
+pub type FileSummaries = Vec<(String, LoadSummary)>;
+
+impl LoadSummary {
+    pub fn report(&self, path: &str, limits: &LoadLimits) {
+        if self.failed == 0 && self.oversized == 0 && !self.truncated {
+            return;
+        }
+        let message = format!(
+            "Loaded {} of {} documents from {}: {} failed to parse, {} over the size limit{}\n\
+             Limits: --max-document-size {}, --max-documents {}",
+            self.loaded,
+            self.documents,
+            path,
+            self.failed,
+            self.oversized,
+            if self.truncated {
+                ", stopped at the document limit"
+            } else {
+                ""
+            },
+            limits
+                .max_document_size
+                .0
+                .map(format_size)
+                .unwrap_or_else(|| "unlimited".to_string()),
+            limits.max_documents
+        );
+        warnings::warn(Warning::new("load-incomplete", message).in_file(path));
+    }
+}
+
+/// Open a catalog file as UTF-8, dropping a UTF-8 byte order mark and
+/// transcoding files with a UTF-16 one
+fn open_source(path: &str) -> io::Result<Box<dyn BufRead>> {
+    decode(BufReader::new(File::open(path)?), path)
+}
+
+/// The UTF-8 text of a catalog file, `path` names it in messages
+fn decode<R: Read + 'static>(mut reader: BufReader<R>, path: &str) -> io::Result<Box<dyn BufRead>> {
+    let start = reader.fill_buf()?;
+    let utf16 = match start {
+        [0xEF, 0xBB, 0xBF, ..] => {
+            reader.consume(3);
+            return Ok(Box::new(reader));
+        }
+        [0xFF, 0xFE, ..] => Some(("UTF-16LE", false)),
+        [0xFE, 0xFF, ..] => Some(("UTF-16BE", true)),
+        _ => None,
+    };
+    let Some((encoding, big_endian)) = utf16 else {
+        return Ok(Box::new(reader));
+    };
+
+    reader.consume(2);
+    warnings::warn(
+        Warning::new(
+            "file-encoding",
+            format!("{} is {}, transcoded to UTF-8", path, encoding),
+        )
+        .in_file(path),
+    );
+    Ok(Box::new(Utf16Reader {
+        reader,
+        encoding,
+        big_endian,
+        carry: Vec::new(),
+        decoded: Vec::new(),
+        position: 0,
+    }))
+}
+
+/// UTF-8 text of a UTF-16 stream, transcoded a buffer at a time so that
+/// --max-document-size still bounds what is held in memory
+struct Utf16Reader<R> {
+    reader: R,
+    encoding: &'static str,
+    big_endian: bool,
+    /// Bytes of a unit or a surrogate pair split across reads
+    carry: Vec<u8>,
+    decoded: Vec<u8>,
+    position: usize,
+}
+
+impl<R: BufRead> Utf16Reader<R> {
+    fn invalid(&self, message: String) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    }
+}
+
+impl<R: BufRead> Read for Utf16Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl<R: BufRead> BufRead for Utf16Reader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.position == self.decoded.len() {
+            let input = self.reader.fill_buf()?;
+            if input.is_empty() {
+                return match self.carry.len() {
+                    0 => Ok(&[]),
+                    1 | 3 => Err(self.invalid(format!("truncated {} content", self.encoding))),
+                    _ => Err(self.invalid(format!(
+                        "invalid {} content: unpaired surrogate at the end",
+                        self.encoding
+                    ))),
+                };
+            }
+            let mut bytes = std::mem::take(&mut self.carry);
+            bytes.extend_from_slice(input);
+            let read = input.len();
+            self.reader.consume(read);
+
+            let unit = |pair: &[u8]| {
+                let pair = [pair[0], pair[1]];
+                if self.big_endian {
+                    u16::from_be_bytes(pair)
+                } else {
+                    u16::from_le_bytes(pair)
+                }
+            };
+            let mut whole = bytes.len() - bytes.len() % 2;
+            // The high half of a surrogate pair waits for the low one
+            if whole >= 2 && (0xD800..0xDC00).contains(&unit(&bytes[whole - 2..whole])) {
+                whole -= 2;
+            }
+            let text = char::decode_utf16(bytes[..whole].chunks_exact(2).map(unit))
+                .collect::<Result<String, _>>()
+                .map_err(|err| {
+                    self.invalid(format!("invalid {} content: {}", self.encoding, err))
+                })?;
+            self.carry = bytes[whole..].to_vec();
+            self.decoded = text.into_bytes();
+            self.position = 0;
+        }
+        Ok(&self.decoded[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.decoded.len());
+    }
+}
+
+/// Load every catalog document of a file, skipping the ones that are over
+/// the limits or don't deserialize
+pub fn load_file(path: &str, limits: &LoadLimits) -> io::Result<(Vec<CatalogEntry>, LoadSummary)> {
+    let mut summary = LoadSummary::default();
+    let mut entries = Vec::new();
+    load_documents(
+        CatalogReader::open(path, limits)?,
+        &mut summary,
+        &mut entries,
+    )?;
+    Ok((entries, summary))
+}
+
+/// Load catalog documents held in memory, `label` names them in messages
+pub fn load_str(
+    content: &str,
+    label: &str,
+    limits: &LoadLimits,
+) -> io::Result<(Vec<CatalogEntry>, LoadSummary)> {
+    let mut summary = LoadSummary::default();
+    let mut entries = Vec::new();
+    load_documents(
+        CatalogReader::new(content.as_bytes(), label, limits),
+        &mut summary,
+        &mut entries,
+    )?;
+    Ok((entries, summary))
+}
+
+/// opm renders JSON catalogs as objects one after the other, which isn't a
+/// single YAML document. Put a document marker in front of every object but
+/// the first, on the object's own line so line numbers stay right.
+fn split_json_stream(content: &str) -> Option<String> {
+    if !content.trim_start().starts_with('{') {
+        return None;
+    }
+    let mut ends = Vec::new();
+    let mut stream =
+        serde_json::Deserializer::from_str(content).into_iter::<serde::de::IgnoredAny>();
+    while let Some(Ok(_)) = stream.next() {
+        ends.push(stream.byte_offset());
+    }
+    if ends.len() < 2 {
+        return None;
+    }
+
+    let mut split = String::with_capacity(content.len() + ends.len() * 4);
+    let mut start = 0;
+    for end in ends {
+        let next = end + (content[end..].len() - content[end..].trim_start().len());
+        split.push_str(&content[start..next]);
+        if next < content.len() {
+            split.push_str(if split.ends_with('\n') {
+                "--- "
+            } else {
+                "\n--- "
+            });
+        }
+        start = next;
+    }
+    split.push_str(&content[start..]);
+    Some(split)
+}
+
+/// Where a catalog document came from
+#[derive(Debug, Clone)]
+pub struct DocumentMeta {
+    /// File of the document, followed by `:name/key` for documents held in
+    /// a ConfigMap or an annotation
+    pub source: String,
+    /// Position in the stream, 0-based, counting the documents nested ones
+    /// were cut out of
+    pub index: u64,
+    /// Line the document starts on, 1-based, within its ConfigMap key or
+    /// annotation for nested ones
+    pub line: usize,
+    /// Size of the document in bytes
+    pub size: u64,
+}
+
+/// Document a CatalogReader couldn't turn into a catalog entry
+#[derive(Debug)]
+pub enum LoadError {
+    /// Reading can't go on: the stream failed, aliases expanded over the
+    /// budget or a placeholder is left with `--placeholder-policy error`.
+    /// Nothing follows.
+    Fatal(io::Error),
+    /// Over --max-document-size, the content got discarded
+    Oversized(DocumentMeta),
+    /// Valid document of a schema other than the OLM ones
+    UnknownSchema {
+        meta: DocumentMeta,
+        schema: String,
+        content: String,
+    },
+    Invalid {
+        meta: DocumentMeta,
+        message: String,
+        content: String,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Fatal(err) => write!(f, "{}", err),
+            LoadError::Oversized(meta) => write!(
+                f,
+                "{}:{}: {} bytes is over the document size limit",
+                meta.source, meta.line, meta.size
+            ),
+            LoadError::UnknownSchema { meta, schema, .. } => {
+                write!(
+                    f,
+                    "{}:{}: unknown schema '{}'",
+                    meta.source, meta.line, schema
+                )
+            }
+            LoadError::Invalid { meta, message, .. } => {
+                write!(f, "{}:{}: {}", meta.source, meta.line, message)
+            }
+        }
+    }
+}
+
+/// Catalog documents of a stream, one item per document, good or not.
+/// opm JSON streams, ConfigMaps and catalogs in annotations are opened up
+/// and their documents come in their place. Stops at --max-documents; warnings that don't skip a
+/// document, like unsubstituted placeholders, go out as they're found.
+pub struct CatalogReader<'a> {
+    limits: &'a LoadLimits,
+    /// Streams being read, nested ones on top, with the source they're
+    /// labeled with
+    streams: Vec<(String, DocumentReader<Box<dyn BufRead + 'a>>)>,
+    /// Files of a directory not opened yet, the next one last
+    files: Vec<String>,
+    documents: u64,
+    dropped: u64,
+    truncated: bool,
+    done: bool,
+}
+
+impl<'a> CatalogReader<'a> {
+    /// Read the catalog at `path` like --file does: a file, a tar archive
+    /// or every catalog file under a directory. Files of a directory are
+    /// opened as reading gets to them, archives are read into memory whole.
+    pub fn open(path: &str, limits: &'a LoadLimits) -> io::Result<CatalogReader<'a>> {
+        let mut reader = CatalogReader::empty(limits);
+        if archive::is_archive(path) {
+            let files = archive::catalog_files(path).map_err(io::Error::other)?;
+            for (label, content) in files.into_iter().rev() {
+                let content = decode(BufReader::new(io::Cursor::new(content)), &label)?;
+                reader.push(label, content);
+            }
+        } else if Path::new(path).is_dir() {
+            catalog_files(Path::new(path), &mut reader.files)?;
+            reader.files.reverse();
+        } else {
+            reader.push(path.to_string(), open_source(path)?);
+        }
+        Ok(reader)
+    }
+
+    /// Read a UTF-8 stream, `source` labels its documents
+    pub fn new(
+        reader: impl BufRead + 'a,
+        source: &str,
+        limits: &'a LoadLimits,
+    ) -> CatalogReader<'a> {
+        let mut catalog_reader = CatalogReader::empty(limits);
+        catalog_reader.push(source.to_string(), reader);
+        catalog_reader
+    }
+
+    fn empty(limits: &'a LoadLimits) -> CatalogReader<'a> {
+        CatalogReader {
+            limits,
+            streams: Vec::new(),
+            files: Vec::new(),
+            documents: 0,
+            dropped: 0,
+            truncated: false,
+            done: false,
+        }
+    }
+
+    fn push(&mut self, source: String, reader: impl BufRead + 'a) {
+        let documents = DocumentReader::new(
+            Box::new(reader) as Box<dyn BufRead + 'a>,
+            self.limits.max_document_size.0,
+        );
+        self.streams.push((source, documents));
+    }
+
+    fn fatal(
+        &mut self,
+        message: String,
+    ) -> Option<Result<(DocumentMeta, CatalogEntry), LoadError>> {
+        self.done = true;
+        Some(Err(LoadError::Fatal(io::Error::new(
+            io::ErrorKind::InvalidData,
+            message,
+        ))))
+    }
+
+    /// Item for one document, None when it got opened up, dropped for
+    /// being of a schema not asked for or isn't a catalog document at all
+    fn parse(
+        &mut self,
+        meta: DocumentMeta,
+        content: Option<Vec<u8>>,
+    ) -> Option<Result<(DocumentMeta, CatalogEntry), LoadError>> {
+        let Some(content) = content else {
+            return Some(Err(LoadError::Oversized(meta)));
+        };
+        let (content, parsed) = match String::from_utf8(content) {
+            Ok(content) => {
+                let content = if content.contains('\r') {
+                    content.replace("\r\n", "\n")
+                } else {
+                    content
+                };
+                if let Some(schemas) = self.limits.schemas {
+                    let unwanted = peek_schema(&content).is_some_and(|schema| {
+                        KNOWN_SCHEMAS.contains(&schema.as_str())
+                            && !schemas.contains(&schema.as_str())
+                    });
+                    if unwanted {
+                        self.dropped += 1;
+                        return None;
+                    }
+                }
+                let content = self.limits.substitutions.apply(&content);
+                let parsed = parse_document(&content, self.limits.max_alias_expansion.0);
+                (content, parsed)
+            }
+            Err(err) => (
+                String::from_utf8_lossy(err.as_bytes()).into_owned(),
+                Err(ParseError::Invalid(format!(
+                    "not UTF-8 text, only UTF-8 and UTF-16 with a byte order mark are read: {}",
+                    err
+                ))),
+            ),
+        };
+
+        match parsed {
+            Ok(entry) => {
+                if let Some(placeholder) = first_placeholder(&content) {
+                    match self.limits.substitutions.policy {
+                        PlaceholderPolicy::Error => {
+                            return self.fatal(format!(
+                                "unsubstituted placeholder {} in the document at {}:{}",
+                                placeholder, meta.source, meta.line
+                            ));
+                        }
+                        PlaceholderPolicy::Warn => warnings::warn(
+                            Warning::new(
+                                "placeholder-unsubstituted",
+                                format!("{} has no value, see --substitute", placeholder),
+                            )
+                            .at(&meta.source, meta.line),
+                        ),
+                        PlaceholderPolicy::Ignore => {}
+                    }
+                }
+                Some(Ok((meta, entry)))
+            }
+            Err(ParseError::UnknownSchema(schema)) => Some(Err(LoadError::UnknownSchema {
+                meta,
+                schema,
+                content,
+            })),
+            // Not worth carrying on, the rest of the file is as untrustworthy
+            Err(ParseError::AliasLimit(err)) => self.fatal(format!(
+                "alias expansion limit exceeded in the document at {}:{}: {}",
+                meta.source, meta.line, err
+            )),
+            Err(ParseError::Invalid(mut message)) => {
+                if let Some(stream) = split_json_stream(&content) {
+                    self.push(meta.source, io::Cursor::new(stream.into_bytes()));
+                    return None;
+                }
+                match manifest::parse(&content) {
+                    Some(Manifest::Inline { name, data }) => {
+                        // On a stack, so the first key goes on last
+                        for (key, value) in data.into_iter().rev() {
+                            let source = format!("{}:{}/{}", meta.source, name, key);
+                            self.push(source, io::Cursor::new(value.into_bytes()));
+                        }
+                        return None;
+                    }
+                    Some(Manifest::CatalogSource { name, image }) if self.limits.follow_image => {
+                        let rendered = match opm::render(&image) {
+                            Ok(rendered) => rendered,
+                            Err(err) => {
+                                return self.fatal(format!(
+                                    "Failed to follow CatalogSource '{}' at {}:{}: {}",
+                                    name, meta.source, meta.line, err
+                                ));
+                            }
+                        };
+                        let source = format!("{}:{}/{}", meta.source, name, image);
+                        self.push(source, io::Cursor::new(rendered));
+                        return None;
+                    }
+                    Some(Manifest::CatalogSource { name, image }) => {
+                        warnings::warn(
+                            Warning::new(
+                                "catalog-source-image",
+                                format!(
+                                    "CatalogSource '{}' serves the catalog image {}, pass --follow-image to render it with opm",
+                                    name, image
+                                ),
+                            )
+                            .at(&meta.source, meta.line),
+                        );
+                        return None;
+                    }
+                    None => {}
+                }
+                if let Some(placeholder) = first_placeholder(&content) {
+                    message = format!("{} (unsubstituted placeholder {})", message, placeholder);
+                }
+                Some(Err(LoadError::Invalid {
+                    meta,
+                    message,
+                    content,
+                }))
+            }
+        }
+    }
+}
+
+impl Iterator for CatalogReader<'_> {
+    type Item = Result<(DocumentMeta, CatalogEntry), LoadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            if self.streams.is_empty() {
+                let file = self.files.pop()?;
+                match open_source(&file) {
+                    Ok(reader) => self.push(file, reader),
+                    Err(err) => return self.fatal(format!("Failed to read {}: {}", file, err)),
+                }
+                continue;
+            }
+            let (source, documents) = self.streams.last_mut()?;
+            let document = match documents.next() {
+                None => {
+                    self.streams.pop();
+                    continue;
+                }
+                Some(Err(err)) => {
+                    self.done = true;
+                    return Some(Err(LoadError::Fatal(err)));
+                }
+                Some(Ok(document)) => document,
+            };
+            let meta = DocumentMeta {
+                source: source.clone(),
+                index: self.documents,
+                line: document.line,
+                size: document.size,
+            };
+            if self
+                .limits
+                .max_documents
+                .0
+                .is_some_and(|max| meta.index >= max)
+            {
+                warnings::warn(
+                    Warning::new(
+                        "document-limit",
+                        format!(
+                            "more than {} documents (--max-documents)",
+                            self.limits.max_documents
+                        ),
+                    )
+                    .at(&meta.source, meta.line),
+                );
+                self.truncated = true;
+                self.done = true;
+                break;
+            }
+            self.documents += 1;
+            if let Some(item) = self.parse(meta, document.content) {
+                return Some(item);
+            }
+        }
+        None
+    }
+}
+
+/// Load the documents of a reader into `entries`, reporting the ones left
+/// out as warnings
+fn load_documents(
+    mut documents: CatalogReader,
+    summary: &mut LoadSummary,
+    entries: &mut Vec<CatalogEntry>,
+) -> io::Result<()> {
+    let limits = documents.limits;
+    for item in documents.by_ref() {
+        let (meta, mut entry) = match item {
+            Ok(item) => item,
+            Err(LoadError::Fatal(err)) => return Err(err),
+            Err(LoadError::Oversized(meta)) => {
+                let message = format!(
+                    "{} bytes is over --max-document-size of {}",
+                    meta.size,
+                    limits
+                        .max_document_size
+                        .0
+                        .map(format_size)
+                        .unwrap_or_default()
+                );
+                warnings::warn(
+                    Warning::new("document-oversized", message.clone()).at(&meta.source, meta.line),
+                );
+                summary.oversized += 1;
+                summary.skipped.push(SkippedDocument {
+                    line: meta.line,
+                    reason: "oversized",
+                    message,
+                    schema: None,
+                    content: None,
+                });
+                continue;
+            }
+            Err(LoadError::UnknownSchema {
+                meta,
+                schema,
+                content,
+            }) => {
+                let message = format!("unknown schema '{}'", schema);
+                warnings::warn(
+                    Warning::new("document-unknown-schema", message.clone())
+                        .at(&meta.source, meta.line),
+                );
+                summary.unknown += 1;
+                summary.skipped.push(SkippedDocument {
+                    line: meta.line,
+                    reason: "unknown-schema",
+                    message,
+                    schema: Some(schema),
+                    content: (!limits.low_memory).then_some(content),
+                });
+                continue;
+            }
+            Err(LoadError::Invalid {
+                meta,
+                message,
+                content,
+            }) => {
+                warnings::warn(
+                    Warning::new("document-invalid", message.clone()).at(&meta.source, meta.line),
+                );
+                summary.failed += 1;
+                summary.skipped.push(SkippedDocument {
+                    line: meta.line,
+                    reason: "invalid",
+                    message,
+                    schema: None,
+                    content: (!limits.low_memory).then_some(content),
+                });
+                continue;
+            }
+        };
+
+        let untidy = names::check(&entry);
+        for name in &untidy {
+            let advice = match limits.trim_names {
+                true => "trimmed",
+                false => "see --trim-names",
+            };
+            warnings::warn(
+                Warning::new(
+                    "name-untidy",
+                    format!("{}: {}, {}", name.location, name, advice),
+                )
+                .at(&meta.source, meta.line),
+            );
+        }
+        if limits.trim_names && !untidy.is_empty() {
+            names::trim(&mut entry);
+        }
+
+        summary.loaded += 1;
+        summary.loaded_bytes += meta.size;
+        let package = match &entry {
+            CatalogEntry::OlmPackage(package) => {
+                summary.packages += 1;
+                &package.name
+            }
+            CatalogEntry::OpmChannel(channel) => {
+                summary.channels += 1;
+                &channel.package
+            }
+            CatalogEntry::OlmBundle(bundle) => {
+                summary.bundles += 1;
+                for (index, property) in bundle.properties.iter().enumerate() {
+                    let Some(reason) = &property.malformed else {
+                        continue;
+                    };
+                    warnings::warn(
+                        Warning::new(
+                            "property-malformed",
+                            format!(
+                                "bundle {} properties[{}] ({}): {}",
+                                bundle.name,
+                                index,
+                                property.display_type(),
+                                reason
+                            ),
+                        )
+                        .at(&meta.source, meta.line),
+                    );
+                }
+                if !limits.low_memory {
+                    summary.bundle_sizes.push(BundleSize {
+                        package: bundle.package.clone(),
+                        name: bundle.name.clone(),
+                        bytes: meta.size,
+                        properties: PropertySizes::of(bundle),
+                    });
+                }
+                &bundle.package
+            }
+            CatalogEntry::OlmDeprecations(deprecations) => {
+                summary.deprecations += 1;
+                &deprecations.package
+            }
+        };
+        summary.package_names.insert(package.clone());
+        entries.push(entry);
+    }
+
+    summary.documents += documents.documents;
+    summary.dropped += documents.dropped;
+    summary.truncated |= documents.truncated;
+    Ok(())
+}
+
+pub fn is_catalog_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| matches!(extension, "yaml" | "yml" | "json"))
+}
+
+// Catalog files under a directory in a stable order, hidden entries are skipped
+fn catalog_files(dir: &Path, files: &mut Vec<String>) -> io::Result<()> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    paths.sort();
+    for path in paths {
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            catalog_files(&path, files)?;
+        } else if is_catalog_file(&path) {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Catalog files behind a --file value, the file itself or those of a directory
+pub fn source_files(path: &str) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    if Path::new(path).is_dir() {
+        catalog_files(Path::new(path), &mut files)
+            .map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    } else {
+        files.push(path.to_string());
+    }
+    Ok(files)
+}
+
+/// Hand the raw bytes of every catalog document of a file to `visit`
+/// without parsing or keeping them. opm JSON streams, ConfigMaps and
+/// catalogs in annotations are opened up like the loader does, their documents come in their place.
+/// Documents over --max-document-size are skipped.
+pub fn stream_documents(
+    path: &str,
+    limits: &LoadLimits,
+    mut visit: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+    if archive::is_archive(path) {
+        let files = archive::catalog_files(path).map_err(io::Error::other)?;
+        for (label, content) in files {
+            let reader = decode(BufReader::new(io::Cursor::new(content)), &label)?;
+            stream_reader(reader, &label, limits, &mut visit)?;
+        }
+        return Ok(());
+    }
+    stream_reader(open_source(path)?, path, limits, &mut visit)
+}
+
+fn stream_reader(
+    reader: impl BufRead,
+    source: &str,
+    limits: &LoadLimits,
+    visit: &mut impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+    let oversized = |document: &RawDocument| {
+        warnings::warn(
+            Warning::new(
+                "document-oversized",
+                format!(
+                    "{} bytes is over --max-document-size of {}",
+                    document.size,
+                    limits
+                        .max_document_size
+                        .0
+                        .map(format_size)
+                        .unwrap_or_default()
+                ),
+            )
+            .at(source, document.line),
+        )
+    };
+    for document in DocumentReader::new(reader, limits.max_document_size.0) {
+        let document = document?;
+        let Some(content) = &document.content else {
+            oversized(&document);
+            continue;
+        };
+        if document_package(content).is_none() {
+            if let Some(nested) = nested_documents(content, source) {
+                for (source, nested) in nested {
+                    stream_reader(io::Cursor::new(nested), &source, limits, visit)?;
+                }
+                continue;
+            }
+        }
+        visit(content)?;
+    }
+    Ok(())
+}
+
+/// Streams held by a document that isn't a catalog document itself: the
+/// objects of an opm JSON stream, or the data keys of a ConfigMap and the
+/// catalogs in annotations labeled like the loader labels them
+fn nested_documents(content: &[u8], source: &str) -> Option<Vec<(String, Vec<u8>)>> {
+    let content = std::str::from_utf8(content).ok()?;
+    if let Some(stream) = split_json_stream(content) {
+        return Some(vec![(source.to_string(), stream.into_bytes())]);
+    }
+    match manifest::parse(content)? {
+        Manifest::Inline { name, data } => Some(
+            data.into_iter()
+                .map(|(key, value)| (format!("{}:{}/{}", source, name, key), value.into_bytes()))
+                .collect(),
+        ),
+        Manifest::CatalogSource { .. } => None,
+    }
+}
+
+#[derive(Deserialize)]
+struct PackageOnly {
+    schema: Option<String>,
+    name: Option<String>,
+    package: Option<String>,
+}
+
+/// Package a raw document belongs to, without deserializing the rest of it
+pub fn document_package(content: &[u8]) -> Option<String> {
+    if let Ok(text) = std::str::from_utf8(content) {
+        if let Some(schema) = top_level_value(text, "schema") {
+            let key = if schema == "olm.package" {
+                "name"
+            } else {
+                "package"
+            };
+            if let Some(package) = top_level_value(text, key) {
+                return Some(package.to_string());
+            }
+        }
+    }
+    let document: PackageOnly = serde_yaml::from_slice(content).ok()?;
+    match document.schema.as_deref()? {
+        "olm.package" => document.name,
+        _ => document.package,
+    }
+}
+
+/// Load a catalog file, a tar archive, or every catalog file under a
+/// directory, reporting what got skipped on stderr. Returns the summary of
+/// every file, files of an archive are named after their path inside it.
+pub fn load_path(
+    path: &str,
+    limits: &LoadLimits,
+) -> Result<(Vec<CatalogEntry>, FileSummaries), String> {
+    let mut entries = Vec::new();
+    let mut summaries = Vec::new();
+    if archive::is_archive(path) {
+        for (label, content) in archive::catalog_files(path)? {
+            let mut summary = LoadSummary::default();
+            let reader = decode(BufReader::new(io::Cursor::new(content)), &label)
+                .map_err(|err| format!("Failed to read {}: {}", label, err))?;
+            load_documents(
+                CatalogReader::new(reader, &label, limits),
+                &mut summary,
+                &mut entries,
+            )
+            .map_err(|err| format!("Failed to read {}: {}", label, err))?;
+            summary.report(&label, limits);
+            summaries.push((label, summary));
+        }
+        return Ok((entries, summaries));
+    }
+    for file in source_files(path)? {
+        let (file_entries, summary) =
+            load_file(&file, limits).map_err(|err| format!("Failed to read {}: {}", file, err))?;
+        summary.report(&file, limits);
+        entries.extend(file_entries);
+        summaries.push((file, summary));
+    }
+    Ok((entries, summaries))
+}
+
+/// Catalog of a `label=path` input, the path doubling as label when unlabeled
+pub struct LabeledCatalog {
+    pub label: String,
+    pub loaded: Result<(Catalog, FileSummaries), String>,
+}
+
+/// Load every input on its own thread, they don't depend on each other,
+/// or one after the other with --low-memory. Results come back in the
+/// order of the inputs.
+pub fn load_labeled(inputs: &[String], limits: &LoadLimits) -> Vec<LabeledCatalog> {
+    let load = |input: &String| {
+        let (label, path) = input.split_once('=').unwrap_or((input, input));
+        let loaded = load_path(path, limits)
+            .map(|(entries, summaries)| (Catalog::from_entries(entries), summaries));
+        LabeledCatalog {
+            label: label.to_string(),
+            loaded,
+        }
+    };
+    if inputs.len() == 1 || limits.low_memory {
+        return inputs.iter().map(load).collect();
+    }
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .iter()
+            .map(|input| scope.spawn(move || load(input)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            })
+            .collect()
+    })
+}
+
+/// Load a catalog file or directory and report what got skipped on stderr
+pub fn load_catalog(path: &str, limits: &LoadLimits) -> Result<Catalog, String> {
+    let (entries, _) = load_path(path, limits)?;
+    Ok(Catalog::from_entries(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROKEN: &str = include_str!("selftest/broken.yaml");
+    const ANCHORS: &str = include_str!("../tests/fixtures/anchors.yaml");
+
+    fn kind(item: &Result<(DocumentMeta, CatalogEntry), LoadError>) -> &'static str {
+        match item {
+            Ok((_, CatalogEntry::OlmPackage(_))) => "package",
+            Ok((_, CatalogEntry::OlmBundle(_))) => "bundle",
+            Ok(_) => "other",
+            Err(LoadError::Invalid { .. }) => "invalid",
+            Err(LoadError::UnknownSchema { .. }) => "unknown",
+            Err(LoadError::Oversized(_)) => "oversized",
+            Err(LoadError::Fatal(_)) => "fatal",
+        }
+    }
+
+    #[test]
+    fn reader_interleaves_errors_with_documents() {
+        let limits = LoadLimits::default();
+        let items: Vec<_> = CatalogReader::new(BROKEN.as_bytes(), "broken.yaml", &limits)
+            .map(|item| {
+                (
+                    item.as_ref()
+                        .map_or_else(|_| None, |(meta, _)| Some(meta.index)),
+                    kind(&item),
+                )
+            })
+            .collect();
+        let kinds: Vec<_> = items.iter().map(|(_, kind)| *kind).collect();
+        assert_eq!(
+            kinds,
+            ["package", "invalid", "invalid", "unknown", "bundle"]
+        );
+        assert_eq!(items[0].0, Some(0));
+        assert_eq!(items[4].0, Some(4));
+    }
+
+    #[test]
+    fn reader_stops_early_and_resumes() {
+        let limits = LoadLimits::default();
+        let mut reader = CatalogReader::new(BROKEN.as_bytes(), "broken.yaml", &limits);
+        let first: Vec<_> = reader.by_ref().take(2).collect();
+        assert_eq!(
+            first.iter().map(kind).collect::<Vec<_>>(),
+            ["package", "invalid"]
+        );
+        match reader.next() {
+            Some(Err(LoadError::Invalid { meta, .. })) => {
+                assert_eq!((meta.source.as_str(), meta.line), ("broken.yaml", 9));
+            }
+            other => panic!(
+                "third document not the invalid channel: {:?}",
+                other.map(|item| kind(&item))
+            ),
+        }
+    }
+
+    #[test]
+    fn reader_stops_at_max_documents() {
+        let limits = LoadLimits {
+            max_documents: Limit(Some(2)),
+            ..LoadLimits::default()
+        };
+        let mut reader = CatalogReader::new(BROKEN.as_bytes(), "broken.yaml", &limits);
+        assert_eq!(reader.by_ref().count(), 2);
+        assert!(reader.truncated);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn document_package_reads_top_level_lines() {
+        let package = |content: &str| document_package(content.as_bytes());
+        assert_eq!(
+            package("schema: olm.package\nname: etcd\n").as_deref(),
+            Some("etcd")
+        );
+        assert_eq!(
+            package("schema: olm.bundle\nname: etcd.v1.0.0\npackage: etcd\n").as_deref(),
+            Some("etcd")
+        );
+        // Quoted values and JSON documents go through the parser
+        assert_eq!(
+            package("schema: olm.channel\npackage: \"etcd\"\nname: stable\n").as_deref(),
+            Some("etcd")
+        );
+        assert_eq!(
+            package("{\"schema\": \"olm.bundle\", \"package\": \"etcd\"}").as_deref(),
+            Some("etcd")
+        );
+        // Nested keys named like top-level ones don't count
+        assert_eq!(
+            package(
+                "schema: olm.bundle\nproperties:\n- value:\n    package: other\npackage: etcd\n"
+            )
+            .as_deref(),
+            Some("etcd")
+        );
+        assert_eq!(package("kind: ConfigMap\n"), None);
+    }
+
+    #[test]
+    fn reader_opens_up_json_streams() {
+        let limits = LoadLimits::default();
+        let stream = "{\"schema\": \"olm.package\", \"name\": \"a\"}\n{\"schema\": \"olm.package\", \"name\": \"b\"}\n";
+        let items: Vec<_> = CatalogReader::new(stream.as_bytes(), "stream.json", &limits).collect();
+        assert_eq!(
+            items.iter().map(kind).collect::<Vec<_>>(),
+            ["package", "package"]
+        );
+    }
+
+    /// `prefix` followed by endless filler, counting the bytes handed out
+    struct Endless {
+        prefix: Vec<u8>,
+        filler: Vec<u8>,
+        served: std::rc::Rc<std::cell::Cell<usize>>,
+    }
+
+    impl Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let served = self.served.get();
+            for (index, byte) in buf.iter_mut().enumerate() {
+                let position = served + index;
+                *byte = match self.prefix.get(position) {
+                    Some(&byte) => byte,
+                    None => self.filler[(position - self.prefix.len()) % self.filler.len()],
+                };
+            }
+            self.served.set(served + buf.len());
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn json_objects_are_cut_out_as_they_are_read() {
+        let limits = LoadLimits {
+            max_document_size: Limit(Some(80)),
+            ..LoadLimits::default()
+        };
+        let big = "x".repeat(100);
+        let stream = format!(
+            "{{\"schema\": \"olm.package\", \"name\": \"a}}\\\"{{\"}}\n\n{{\"schema\": \"olm.package\", \"description\": \"{}\"}}\n{{\"schema\": \"olm.package\",\n \"name\": \"b\"}}",
+            big
+        );
+        let items: Vec<_> = CatalogReader::new(stream.as_bytes(), "stream.json", &limits)
+            .map(|item| {
+                let line = match &item {
+                    Ok((meta, _)) | Err(LoadError::Oversized(meta)) => meta.line,
+                    _ => 0,
+                };
+                (kind(&item), line)
+            })
+            .collect();
+        assert_eq!(items, [("package", 1), ("oversized", 3), ("package", 4)]);
+    }
+
+    #[test]
+    fn oversized_json_object_is_rejected_without_reading_it_whole() {
+        let limits = LoadLimits {
+            max_document_size: Limit(Some(1 << 10)),
+            ..LoadLimits::default()
+        };
+        let served = std::rc::Rc::new(std::cell::Cell::new(0));
+        let endless = Endless {
+            prefix: b"{\"schema\": \"olm.package\", \"description\": \"".to_vec(),
+            filler: b"x".to_vec(),
+            served: served.clone(),
+        };
+        let mut reader = CatalogReader::new(BufReader::new(endless), "endless.json", &limits);
+        assert_eq!(reader.next().as_ref().map(kind), Some("oversized"));
+        assert!(served.get() <= 16 << 10, "read {} bytes", served.get());
+    }
+
+    #[test]
+    fn utf16_is_transcoded_a_buffer_at_a_time() {
+        let served = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut prefix = vec![0xFF, 0xFE];
+        prefix.extend(
+            "schema: olm.package\ndescription: \""
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        // A surrogate pair, so pairs get split across reads too
+        let filler = "\u{1F600}"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let endless = Endless {
+            prefix,
+            filler,
+            served: served.clone(),
+        };
+        let mut decoded = decode(BufReader::new(endless), "endless.yaml").unwrap();
+        let mut start = vec![0; 1 << 10];
+        decoded.read_exact(&mut start).unwrap();
+        let expected = format!(
+            "schema: olm.package\ndescription: \"{}",
+            "\u{1F600}".repeat(200)
+        );
+        assert!(start.starts_with(expected.as_bytes()));
+        assert!(served.get() <= 16 << 10, "read {} bytes", served.get());
+    }
+
+    #[test]
+    fn anchored_skips_and_skip_range_expand_into_entries() {
+        let limits = LoadLimits::default();
+        let channel = CatalogReader::new(ANCHORS.as_bytes(), "anchors.yaml", &limits)
+            .find_map(|item| match item {
+                Ok((_, CatalogEntry::OpmChannel(channel))) => Some(channel),
+                _ => None,
+            })
+            .expect("channel loads");
+        let skipped = ["anchored.v1.0.1", "anchored.v1.0.2"];
+        let [first, second, third] = &channel.entries[..] else {
+            panic!("3 entries: {:?}", channel.entries);
+        };
+        assert_eq!(first.skips, skipped);
+        assert_eq!(second.skips, skipped);
+        assert_eq!(first.skip_range.as_deref(), Some(">=1.0.0 <1.2.0"));
+        assert_eq!(second.skip_range, first.skip_range);
+        assert_eq!(third.skips, [">=1.0.0 <1.2.0"]);
+        assert_eq!(second.replaces, "anchored.v1.2.0");
+    }
+
+    #[test]
+    fn alias_expansion_over_the_budget_stops_reading() {
+        let limits = LoadLimits {
+            max_alias_expansion: Limit(Some(50)),
+            ..LoadLimits::default()
+        };
+        let mut hostile = String::from(
+            "schema: olm.channel\nname: stable\npackage: a\nentries: []\nl0: &l0 [x, x, x, x]\n",
+        );
+        for level in 1..4 {
+            hostile.push_str(&format!(
+                "l{level}: &l{level} [*l{prev}, *l{prev}, *l{prev}, *l{prev}]\n",
+                level = level,
+                prev = level - 1
+            ));
+        }
+        let items: Vec<_> =
+            CatalogReader::new(hostile.as_bytes(), "hostile.yaml", &limits).collect();
+        match &items[..] {
+            [Err(LoadError::Fatal(message))] => {
+                assert!(
+                    message
+                        .to_string()
+                        .contains("alias expansion limit exceeded"),
+                    "{}",
+                    message
+                )
+            }
+            other => panic!(
+                "not one fatal error: {:?}",
+                other.iter().map(kind).collect::<Vec<_>>()
+            ),
+        }
+
+        let limits = LoadLimits {
+            max_alias_expansion: Limit(None),
+            ..LoadLimits::default()
+        };
+        let items: Vec<_> =
+            CatalogReader::new(hostile.as_bytes(), "hostile.yaml", &limits).collect();
+        // Without a budget it's a channel with some unknown fields
+        assert_eq!(items.iter().map(kind).collect::<Vec<_>>(), ["other"]);
+    }
+
+    fn encoding_fixture(name: &str) -> String {
+        format!(
+            "{}/tests/fixtures/encodings/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        )
+    }
+
+    #[test]
+    fn byte_order_marks_and_crlf_load_like_utf8() {
+        let limits = LoadLimits::default();
+        let (expected, _) = load_file(&encoding_fixture("utf8-bom.yaml"), &limits).unwrap();
+        let expected = serde_yaml::to_string(&expected).unwrap();
+        assert!(expected.contains("Opérateur"), "{}", expected);
+        for name in ["utf16le.yaml", "utf16be.yaml", "crlf.yaml"] {
+            let (entries, summary) = load_file(&encoding_fixture(name), &limits).unwrap();
+            assert_eq!((summary.loaded, summary.failed), (2, 0), "{}", name);
+            assert_eq!(
+                serde_yaml::to_string(&entries).unwrap(),
+                expected,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn undecodable_files_name_the_encoding() {
+        let limits = LoadLimits::default();
+        let err = load_file(&encoding_fixture("utf16le-truncated.yaml"), &limits).unwrap_err();
+        assert_eq!(err.to_string(), "truncated UTF-16LE content");
+
+        // Without a byte order mark the text has to be UTF-8
+        let mut reader = CatalogReader::open(&encoding_fixture("latin1.yaml"), &limits).unwrap();
+        match reader.next() {
+            Some(Err(LoadError::Invalid { message, .. })) => {
+                assert!(message.starts_with("not UTF-8 text"), "{}", message)
+            }
+            other => panic!("not invalid: {:?}", other.map(|item| kind(&item))),
+        }
+    }
+}
@@ -1,84 +1,21 @@
-use clap::{Parser, Subcommand};
-use serde::Deserialize;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use schemars::JsonSchema;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
+use std::io::{self, Read};
 
-/// YAML Document Types
-#[derive(Debug, Deserialize)]
-#[serde(tag = "schema")]
-enum CatalogEntry {
-    #[serde(rename = "olm.package")]
-    OlmPackage(Package),
-    #[serde(rename = "olm.channel")]
-    OpmChannel(Channel),
-    #[serde(rename = "olm.bundle")]
-    OlmBundle(Bundle),
-}
-
-#[derive(Debug, Deserialize)]
-struct Package {
-    name: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChannelEntry {
-    name: String,
-    #[serde(default)]
-    replaces: String,
-    #[serde(default)]
-    skips: Vec<String>,
-    #[serde(rename = "SkipRange")]
-    skip_range: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct Channel {
-    name: String,
-    package: String,
-    entries: Vec<ChannelEntry>,
-}
-
-// Implement a custom fucntion for Channel to print the entries
-impl std::fmt::Display for Channel {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Channel: {}\n  Package: {}\n  Entries:",
-            self.name, self.package
-        )?;
-        for entry in &self.entries {
-            write!(f, "\n    - {}", entry.name)?;
-            if entry.replaces != "" {
-                write!(f, "\n      replaces: {}", entry.replaces)?;
-            }
-            if entry.skips.len() > 0 {
-                write!(f, "\n      skips: {:?}", entry.skips)?;
-            }
-            if let Some(range) = &entry.skip_range {
-                write!(f, "\n      skip_range: {}", range)?;
-            }
-        }
-        Ok(())
-    }
-}
-
-#[derive(Debug, Deserialize)]
-struct Bundle {
-    name: String,
-    image: String,
-    package: String,
-}
+use catalog_browser::{
+    advice, annotations, batch, catalog, channel_diff, closure, config, dump, duplicates,
+    entry_table, examples, explain, graph, gvk, health, id, identical, imageset, labels, loader,
+    ocp, opm, output, pager, resolve, select, selftest, stats, template, timestamp, upgrade_path,
+    validate, warnings, write,
+};
 
-// Create a function for Bundle struct to implement the Display trait
-impl std::fmt::Display for Bundle {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Bundle: {}\n  Image: {}\n  Package: {}",
-            self.name, self.image, self.package
-        )
-    }
-}
+use batch::Batch;
+use catalog::{Catalog, CatalogEntry, Channel, OrderedChannel};
+use labels::label;
+use warnings::Warning;
 
 /// CLI Arguments
 #[derive(Parser)]
@@ -88,12 +25,103 @@ impl std::fmt::Display for Bundle {
     about = "CLI to browse catalog.yaml files"
 )]
 struct Cli {
-    /// Path to the catalog.yaml file
+    /// Path to the catalog.yaml file, a directory of catalog files or a
+    /// .tar or .tar.gz archive of them, optionally labeled as `label=path`.
+    /// Repeat to load several catalogs, in parallel, which are merged in
+    /// order for commands that don't understand labels.
     #[arg(short, long)]
-    file: String,
+    file: Vec<String>,
+
+    /// Skip documents larger than this, `unlimited` for trusted inputs
+    #[arg(long, default_value = "64MiB", global = true)]
+    max_document_size: loader::Limit,
+
+    /// Stop loading after this many documents, `unlimited` for trusted inputs
+    #[arg(long, default_value = "unlimited", global = true)]
+    max_documents: loader::Limit,
+
+    /// Abort when YAML aliases expand a document to more nodes than this
+    #[arg(long, default_value = "100k", global = true)]
+    max_alias_expansion: loader::Limit,
+
+    /// Replace `{{ key }}` placeholders of partially rendered catalogs before
+    /// parsing (repeatable)
+    #[arg(long, value_name = "KEY=VALUE", global = true)]
+    substitute: Vec<String>,
+
+    /// YAML file with placeholder values, nested keys are joined with dots
+    #[arg(long, value_name = "FILE", global = true)]
+    substitute_file: Option<String>,
+
+    /// What to do with placeholders left after substitution
+    #[arg(long, value_enum, default_value = "warn", global = true)]
+    placeholder_policy: template::PlaceholderPolicy,
+
+    /// Strip whitespace around and control characters in names, packages,
+    /// replaces and skips while loading. Needed to write out a catalog
+    /// that has them, since the result differs from the source.
+    #[arg(long, global = true)]
+    trim_names: bool,
+
+    /// Render the catalog image of grpc CatalogSource manifests with
+    /// `opm render` and load it, needs opm on PATH
+    #[arg(long, global = true)]
+    follow_image: bool,
+
+    /// Keep as little of the catalog in memory as the command allows: skip
+    /// documents of schemas it doesn't look at, don't keep the text of
+    /// skipped documents or per-bundle sizes and load --file catalogs one
+    /// after the other. `extract` streams like --stream for a single --file
+    /// unless entries get sorted, names trimmed or placeholders substituted.
+    /// Prints how much got retained on stderr.
+    #[arg(long, global = true)]
+    low_memory: bool,
+
+    /// Print additional details
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Whether versions derived leniently from non-semver bundle names take
+    /// part in sorting and analysis
+    #[arg(long, value_enum, default_value = "strict", global = true)]
+    version_scheme: resolve::VersionScheme,
+
+    /// key=value file re-labeling the field names and table headers of text
+    /// output, JSON and CSV keep theirs
+    #[arg(long, value_name = "FILE", global = true)]
+    labels: Option<String>,
 
+    /// Wrap text and fit tables to this many columns instead of the terminal width
+    #[arg(long, global = true)]
+    width: Option<usize>,
+
+    /// Report non-fatal issues as text lines or as one JSON document on stderr
+    #[arg(long, value_enum, default_value = "text", global = true)]
+    warnings_format: warnings::WarningsFormat,
+
+    /// Require this JSON output API version, e.g. catalog-browser/v1
+    #[arg(long, global = true)]
+    api_version: Option<String>,
+
+    /// Don't pipe long output through $PAGER
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// Fail unless every --file catalog has the same content, e.g. the
+    /// per-architecture copies of one catalog image loaded as
+    /// `-f amd64=amd64.yaml -f arm64=arm64.yaml`. Images aren't pulled:
+    /// render each platform's copy first and label it after the platform.
+    #[arg(long, global = true)]
+    require_identical: bool,
+
+    /// Abort when one of several --file catalogs fails to load instead of
+    /// going on with the others
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Without a subcommand, the config file's defaultCommand runs, or an overview
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 #[derive(Subcommand)]
 enum Commands {
@@ -101,6 +129,14 @@ enum Commands {
     List {
         #[arg(value_enum)]
         content_type: ContentType,
+
+        /// Order of listed bundles
+        #[arg(long, value_enum, default_value = "source")]
+        sort: ListSort,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
     },
     /// Show details of specific content
     Show {
@@ -109,10 +145,450 @@ enum Commands {
         content_type: ContentType,
 
         /// Name of the content to show
-        name: String,
+        #[arg(required_unless_present = "from_file")]
+        name: Option<String>,
+
+        /// Show every name listed in a file, one per line, `-` reads stdin.
+        /// Exits with 5 when some of the names aren't found
+        #[arg(long, value_name = "FILE", conflicts_with = "name")]
+        from_file: Option<String>,
+
+        /// Print channel entries in upgrade order, from the tail to the head
+        #[arg(long)]
+        ordered: bool,
+
+        /// Print only channel entry names, one per line
+        #[arg(long)]
+        entries_only: bool,
+
+        /// Print the OpenShift versions supported by each bundle of a package
+        #[arg(long)]
+        ocp_matrix: bool,
+
+        /// Compare CSV annotations across the bundles of a package
+        #[arg(long, conflicts_with = "ocp_matrix")]
+        annotations: bool,
+
+        /// Annotation to compare with --annotations (repeatable), a set of
+        /// feature annotations when omitted
+        #[arg(long, value_name = "KEY", requires = "annotations")]
+        annotation: Vec<String>,
+
+        /// Channels listed by `show package` before the rest get collapsed
+        #[arg(long, default_value_t = 20)]
+        max_channels: usize,
+
+        /// List every channel of a package, however many there are
+        #[arg(long)]
+        all_channels: bool,
+
+        /// Columns of the channel entry table, e.g. `name,version,deprecated`
+        #[arg(long, value_enum, value_delimiter = ',')]
+        columns: Vec<entry_table::Column>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: ShowOutput,
+    },
+    /// Check the catalog for common mistakes
+    Validate {
+        /// Enable an opt-in rule (repeatable)
+        #[arg(long, value_name = "RULE")]
+        enable: Vec<String>,
+
+        /// Skip a rule, including default ones (repeatable)
+        #[arg(long, value_name = "RULE")]
+        disable: Vec<String>,
+
+        /// Run only these rules, e.g. `--rules shared-bundle-image,replaces-in-skips`
+        #[arg(long, value_delimiter = ',', value_name = "RULE,RULE")]
+        rules: Vec<String>,
+
+        /// Stop at the first error finding, by location within the rule reporting it
+        #[arg(long)]
+        fail_fast: bool,
+
+        /// Only validate this package, or the ones matching a glob pattern
+        /// like `*-operator` (repeatable)
+        #[arg(long)]
+        package: Vec<String>,
+
+        /// Only validate this package, taken literally even with glob characters (repeatable)
+        #[arg(long, value_name = "NAME")]
+        exact_package: Vec<String>,
+
+        /// List the available rules and exit
+        #[arg(long)]
+        list_rules: bool,
+
+        /// Show at most this many findings of each severity, 0 for all.
+        /// Defaults to 200 for text output and all for JSON.
+        #[arg(long, value_name = "N")]
+        max_findings: Option<usize>,
+
+        /// Summarize the findings instead of listing them
+        #[arg(long, value_enum)]
+        group_by: Option<validate::GroupBy>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    /// Explain why a bundle is or isn't installable from a channel
+    Explain {
+        #[arg(long)]
+        package: String,
+
+        #[arg(long)]
+        channel: String,
+
+        /// Bundle that should be installed
+        #[arg(long)]
+        bundle: String,
+
+        /// Bundle currently installed on the cluster
+        #[arg(long)]
+        installed: Option<String>,
+
+        /// OpenShift version of the cluster, e.g. 4.14
+        #[arg(long)]
+        ocp_version: Option<String>,
+    },
+    /// Check whether the APIs of a package collide with other packages
+    CheckConflicts {
+        /// Package of the loaded catalog, or a catalog file with the candidate packages
+        #[arg(long)]
+        against: String,
+    },
+    /// Write the documents of selected packages to a new catalog file.
+    ///
+    /// Packages missing from the catalog are reported and the others still
+    /// written, the exit code is then 5.
+    Extract {
+        /// Package to extract, or a glob pattern like `redhat-*` (repeatable)
+        #[arg(long, required_unless_present = "exact_package")]
+        package: Vec<String>,
+
+        /// Package to extract, taken literally even with glob characters (repeatable)
+        #[arg(long, value_name = "NAME")]
+        exact_package: Vec<String>,
+
+        /// Output file
+        #[arg(long)]
+        out: String,
+
+        /// Order of channel entries in the output
+        #[arg(long, value_enum, default_value = "source")]
+        sort_entries: write::SortEntries,
+
+        /// Copy the matching documents straight from the files, byte for byte,
+        /// without loading the catalog first. Much less memory on big indexes.
+        #[arg(long, conflicts_with = "sort_entries")]
+        stream: bool,
+
+        /// Print what would be written, as a diff against the existing file,
+        /// without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Run `opm validate` on the output when opm is on PATH, failing if
+        /// it rejects what was written
+        #[arg(long, conflicts_with = "dry_run")]
+        verify_with_opm: bool,
+    },
+    /// Merge other catalog files into the loaded one and write the result.
+    ///
+    /// Files that fail to load are reported and left out of the merge, the
+    /// exit code is then 5.
+    Merge {
+        /// Catalog file merged on top of the loaded one (repeatable)
+        #[arg(long = "with", required = true, value_name = "FILE")]
+        with: Vec<String>,
+
+        /// Output file
+        #[arg(long)]
+        out: String,
+
+        /// Order of channel entries in the output
+        #[arg(long, value_enum, default_value = "source")]
+        sort_entries: write::SortEntries,
+
+        /// Print what would be written, as a diff against the existing file,
+        /// without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Run `opm validate` on the output when opm is on PATH, failing if
+        /// it rejects what was written
+        #[arg(long, conflicts_with = "dry_run")]
+        verify_with_opm: bool,
+    },
+    /// Compare the entries and edges of a channel with another channel, or
+    /// with the same channel of another labeled catalog
+    ChannelDiff {
+        #[arg(long)]
+        package: String,
+
+        #[arg(long)]
+        channel: String,
+
+        /// Channel of the same package to compare with
+        #[arg(long, conflicts_with = "between", required_unless_present = "between")]
+        other_channel: Option<String>,
+
+        /// Two catalog labels to compare the channel between, e.g. `4.15,4.16`
+        #[arg(long, value_delimiter = ',', value_name = "LABEL,LABEL")]
+        between: Vec<String>,
+    },
+    /// List the APIs (GVKs) of the catalog with the bundles providing them
+    Apis {
+        /// Report overlapping GVK ownership by severity instead. Exits with 1
+        /// when the worst finding is a warning and 2 when it's an error.
+        #[arg(long)]
+        conflicts: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    /// Find the shortest upgrade path between two bundles of a channel
+    Path {
+        #[arg(long)]
+        package: String,
+
+        #[arg(long)]
+        channel: String,
+
+        /// Bundle to upgrade from, it doesn't have to be in the channel
+        #[arg(long)]
+        from: String,
+
+        /// Bundle to upgrade to
+        #[arg(long)]
+        to: String,
+    },
+    /// Packages required by the given ones, recursively, and their images
+    Closure {
+        /// Package to start from, or a glob pattern (repeatable)
+        #[arg(long, required_unless_present = "exact_package")]
+        package: Vec<String>,
+
+        /// Package to start from, taken literally even with glob characters (repeatable)
+        #[arg(long, value_name = "NAME")]
+        exact_package: Vec<String>,
+
+        /// Follow requirements at most this many levels deep
+        #[arg(long)]
+        max_depth: Option<usize>,
+    },
+    /// Print the whole parsed model, including the documents that were skipped
+    Dump {
+        #[arg(long, value_enum, default_value = "json")]
+        output: dump::DumpFormat,
+    },
+    /// Print document counts of the loaded catalogs
+    Stats {
+        /// Break the counts down by source file
+        #[arg(long)]
+        by_file: bool,
+
+        /// Rank the N bundles with the largest source documents instead, with
+        /// the bytes taken by their embedded objects and CSV metadata
+        #[arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            default_missing_value = "10",
+            conflicts_with = "by_file"
+        )]
+        largest: Option<usize>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    /// One line per package with ok/warn/fail results of cheap checks.
+    ///
+    /// Exits with 1 when any package fails, `validate` has the details.
+    Health {
+        /// Only list packages in this state
+        #[arg(long, value_enum)]
+        only: Option<health::Status>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    /// Print the upgrade edges of the catalog
+    Graph {
+        /// Only edges of this package, or of the ones matching a glob pattern (repeatable)
+        #[arg(long)]
+        package: Vec<String>,
+
+        /// Only edges of this package, taken literally even with glob characters (repeatable)
+        #[arg(long, value_name = "NAME")]
+        exact_package: Vec<String>,
+
+        /// Only edges of channels with this name
+        #[arg(long)]
+        channel: Option<String>,
+
+        #[arg(long, value_enum, default_value = "edges-csv")]
+        format: graph::GraphFormat,
+    },
+    /// Look for catalog-wide patterns
+    Analyze {
+        #[command(subcommand)]
+        target: AnalyzeTarget,
+    },
+    /// Generate configuration for other tools from the catalog
+    Generate {
+        #[command(subcommand)]
+        target: GenerateTarget,
+    },
+    /// Check this build against built-in catalogs, doesn't need --file
+    Selftest,
+    /// Print invocations answering common questions, doesn't need --file
+    Examples,
+    /// Print the JSON Schema of a document printed with --output json,
+    /// doesn't need --file
+    #[command(hide = true)]
+    Schema {
+        #[arg(value_enum)]
+        document: SchemaDocument,
+    },
+}
+
+/// JSON documents with a published schema
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum SchemaDocument {
+    /// list packages
+    ListPackages,
+    /// list channels
+    ListChannels,
+    /// list bundles
+    ListBundles,
+    /// show package
+    ShowPackage,
+    /// show channel
+    ShowChannel,
+    /// show bundle
+    ShowBundle,
+    /// validate
+    Validate,
+    /// validate --group-by rule
+    ValidateGroups,
+    /// stats
+    Stats,
+    /// health
+    Health,
+    /// show package --ocp-matrix
+    ShowOcpMatrix,
+    /// show package --annotations
+    ShowAnnotations,
+    /// show --from-file
+    ShowFromFile,
+    /// apis
+    Apis,
+    /// apis --conflicts
+    ApisConflicts,
+    /// dump
+    Dump,
+    /// stats --largest
+    StatsLargest,
+    /// analyze duplicates
+    AnalyzeDuplicates,
+    /// the --warnings-format json document on stderr
+    Warnings,
+}
+
+fn schema_handler(document: SchemaDocument) -> Result<(), Box<dyn std::error::Error>> {
+    use id::Identified;
+    use output::{json_schema, Items};
+    let schema = match document {
+        SchemaDocument::ListPackages => json_schema::<Items<Vec<PackageListItem>>>("PackageList"),
+        SchemaDocument::ListChannels => json_schema::<Items<Vec<ChannelListItem>>>("ChannelList"),
+        SchemaDocument::ListBundles => json_schema::<Items<Vec<BundleListItem>>>("BundleList"),
+        SchemaDocument::ShowPackage => {
+            json_schema::<Items<Vec<Identified<catalog::Package>>>>("PackageList")
+        }
+        SchemaDocument::ShowChannel => {
+            json_schema::<Items<Vec<Identified<Channel>>>>("ChannelList")
+        }
+        SchemaDocument::ShowBundle => {
+            json_schema::<Items<Vec<Identified<catalog::Bundle>>>>("BundleList")
+        }
+        SchemaDocument::Validate => json_schema::<ValidationReport>("ValidationReport"),
+        SchemaDocument::ValidateGroups => json_schema::<ValidationGroups>("ValidationGroups"),
+        SchemaDocument::Stats => json_schema::<stats::Stats>("CatalogStats"),
+        SchemaDocument::Health => {
+            json_schema::<Items<Vec<health::PackageHealth>>>("PackageHealthList")
+        }
+        SchemaDocument::ShowOcpMatrix => json_schema::<Items<Vec<OcpMatrixItem>>>("OcpMatrix"),
+        SchemaDocument::ShowAnnotations => {
+            json_schema::<annotations::AnnotationMatrix>("AnnotationMatrix")
+        }
+        SchemaDocument::ShowFromFile => json_schema::<Items<Vec<BatchItem>>>("ShowResults"),
+        SchemaDocument::Apis => json_schema::<Items<Vec<ApiItem>>>("ApiList"),
+        SchemaDocument::ApisConflicts => json_schema::<ApiConflictReport>("ApiConflictReport"),
+        SchemaDocument::Dump => json_schema::<dump::Dump>("CatalogDump"),
+        SchemaDocument::StatsLargest => {
+            json_schema::<Items<Vec<stats::LargeBundle>>>("BundleSizeList")
+        }
+        SchemaDocument::AnalyzeDuplicates => {
+            json_schema::<Items<Vec<duplicates::DuplicatePair>>>("DuplicateList")
+        }
+        SchemaDocument::Warnings => json_schema::<Items<Vec<Warning>>>("WarningList"),
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+#[derive(Subcommand)]
+enum AnalyzeTarget {
+    /// Package pairs sharing most of their bundle images, e.g. rebrands of one operator
+    Duplicates {
+        /// Report pairs sharing at least this percentage of the smaller
+        /// package's images
+        #[arg(long, default_value = "80", value_parser = clap::value_parser!(u8).range(1..=100))]
+        threshold: u8,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
     },
 }
-#[derive(clap::ValueEnum, Clone)]
+
+#[derive(Subcommand)]
+enum GenerateTarget {
+    /// oc-mirror ImageSetConfiguration for a package
+    Imageset {
+        /// Package to mirror
+        #[arg(long)]
+        package: String,
+
+        /// Channel to mirror, all channels of the package when omitted
+        #[arg(long)]
+        channel: Option<String>,
+
+        /// Version range used for min/max bounds, the channel head when omitted
+        #[arg(long)]
+        version_range: Option<String>,
+
+        /// Catalog image reference put into the configuration
+        #[arg(long)]
+        catalog_image: String,
+
+        /// Output file
+        #[arg(long)]
+        out: String,
+
+        /// Print what would be written, as a diff against the existing file,
+        /// without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
 enum ContentType {
     Packages,
     Channels,
@@ -120,38 +596,206 @@ enum ContentType {
     Package,
     Channel,
     Bundle,
+    /// Object by its stable identifier, e.g. `pkg:etcd/channel:stable`
+    Id,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+enum ListSort {
+    /// Order of the catalog
+    Source,
+    /// Oldest `createdAt` annotation first, bundles without one last
+    Created,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+enum ShowOutput {
+    Text,
+    /// Columns instead of prose, channel entries get one row each
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct PackageListItem<'a> {
+    id: String,
+    name: &'a str,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ChannelListItem<'a> {
+    id: String,
+    name: &'a str,
+    package: &'a str,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct BundleListItem<'a> {
+    id: String,
+    name: &'a str,
+    package: &'a str,
+    image: &'a str,
+    version: Option<String>,
+    /// Strategy the version was resolved with
+    version_source: &'static str,
+    /// Package-qualified names of the channels containing the bundle
+    channels: Vec<String>,
+    /// `createdAt` annotation normalized to UTC, None when missing or unparseable
+    created_at: Option<timestamp::Timestamp>,
+}
+
+/// Packages with a package document, sorted by name
+fn listed_packages(catalog: &Catalog) -> Vec<&str> {
+    catalog
+        .package_names()
+        .into_iter()
+        .filter(|name| catalog.packages.contains_key(*name))
+        .collect()
+}
+
+/// Channels of the catalog by package name, each package's in source order
+fn listed_channels(catalog: &Catalog) -> Vec<&Channel> {
+    catalog
+        .package_names()
+        .into_iter()
+        .flat_map(|package| catalog.channels_of(package))
+        .collect()
+}
+
+/// Bundles of the catalog in the requested order
+fn listed_bundles(catalog: &Catalog, sort: ListSort) -> Vec<&catalog::Bundle> {
+    let mut bundles: Vec<&catalog::Bundle> = catalog
+        .package_names()
+        .into_iter()
+        .flat_map(|package| catalog.bundles_of(package))
+        .collect();
+    if sort == ListSort::Created {
+        // sort_by_key is stable, bundles without a timestamp stay at the end
+        // by package name and source order
+        bundles.sort_by_key(|bundle| (bundle.created_at().is_none(), bundle.created_at()));
+    }
+    bundles
+}
+
+fn list_json(
+    content_type: ContentType,
+    sort: ListSort,
+    catalog: &Catalog,
+) -> Result<String, serde_json::Error> {
+    match content_type {
+        ContentType::Packages => {
+            let items: Vec<PackageListItem> = listed_packages(catalog)
+                .into_iter()
+                .map(|name| PackageListItem {
+                    id: id::ObjectId::package(name).to_string(),
+                    name,
+                })
+                .collect();
+            output::json_document("PackageList", output::Items { items })
+        }
+        ContentType::Channels => {
+            let items: Vec<ChannelListItem> = listed_channels(catalog)
+                .into_iter()
+                .map(|channel| ChannelListItem {
+                    id: id::ObjectId::channel(&channel.package, &channel.name).to_string(),
+                    name: &channel.name,
+                    package: &channel.package,
+                })
+                .collect();
+            output::json_document("ChannelList", output::Items { items })
+        }
+        ContentType::Bundles => {
+            let items: Vec<BundleListItem> = listed_bundles(catalog, sort)
+                .into_iter()
+                .map(|bundle| {
+                    (
+                        bundle,
+                        catalog.resolve_version(&bundle.package, &bundle.name),
+                    )
+                })
+                .map(|(bundle, resolved)| BundleListItem {
+                    id: id::ObjectId::bundle(&bundle.package, &bundle.name).to_string(),
+                    name: &bundle.name,
+                    package: &bundle.package,
+                    image: &bundle.image,
+                    version: resolved.version.map(|version| version.to_string()),
+                    version_source: resolved.source.as_str(),
+                    channels: catalog
+                        .channels_of(&bundle.package)
+                        .filter(|channel| {
+                            channel
+                                .entries
+                                .iter()
+                                .any(|entry| entry.name == bundle.name)
+                        })
+                        .map(|channel| format!("{}/{}", channel.package, channel.name))
+                        .collect(),
+                    created_at: bundle.created_at(),
+                })
+                .collect();
+            output::json_document("BundleList", output::Items { items })
+        }
+        _ => output::json_document("List", output::Items { items: [(); 0] }),
+    }
 }
 
 fn list_handler(
     content_type: ContentType,
-    packages: &HashMap<String, CatalogEntry>,
-    channels: &HashMap<String, Vec<CatalogEntry>>,
-    bundles: &HashMap<String, Vec<CatalogEntry>>,
-) {
+    sort: ListSort,
+    output: OutputFormat,
+    verbose: bool,
+    catalog: &Catalog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if output == OutputFormat::Json {
+        println!("{}", list_json(content_type, sort, catalog)?);
+        return Ok(());
+    }
+
     match content_type {
         ContentType::Packages => {
-            println!("Packages:");
-            for package in packages.keys() {
+            println!("{}:", label("packages"));
+            for package in listed_packages(catalog) {
                 println!("- {}", package);
             }
         }
         ContentType::Channels => {
-            println!("Channels:");
-            for entries in channels.values() {
-                for entry in entries {
-                    if let CatalogEntry::OpmChannel(channel) = entry {
-                        println!("- {}", channel.name);
-                    }
-                }
+            println!("{}:", label("channels"));
+            for channel in listed_channels(catalog) {
+                println!("- {}", channel.name);
             }
         }
         ContentType::Bundles => {
-            println!("Bundles:");
-            for entries in bundles.values() {
-                for bundle in entries {
-                    if let CatalogEntry::OlmBundle(bundle) = bundle {
-                        println!("- {}", bundle.name);
-                    }
+            println!("{}:", label("bundles"));
+            for bundle in listed_bundles(catalog, sort) {
+                let mut details = Vec::new();
+                if verbose {
+                    let resolved = catalog.resolve_version(&bundle.package, &bundle.name);
+                    details.push(
+                        resolved
+                            .version
+                            .map(|version| version.to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                    );
+                    details.push(resolved.source.to_string());
+                }
+                if sort == ListSort::Created {
+                    details.push(match bundle.created_at() {
+                        Some(created) => format!("created {}", created),
+                        None => "created unknown".to_string(),
+                    });
+                }
+                if details.is_empty() {
+                    println!("- {}", bundle.name);
+                } else {
+                    println!("- {} ({})", bundle.name, details.join(", "));
                 }
             }
         }
@@ -159,98 +803,1627 @@ fn list_handler(
             println!("Unsupported content type");
         }
     }
+
+    Ok(())
 }
 
-fn show_handler(
+struct ShowOptions {
+    verbose: bool,
+    ordered: bool,
+    entries_only: bool,
+    ocp_matrix: bool,
+    /// Annotation keys to compare, None unless --annotations is given
+    annotations: Option<Vec<String>>,
+    /// Columns of the channel entry table
+    columns: Vec<entry_table::Column>,
+    /// Channels listed per package, None for all of them
+    max_channels: Option<usize>,
+    output: ShowOutput,
+}
+
+fn print_ocp_matrix(package: &str, catalog: &Catalog, output: ShowOutput) {
+    let headers = ["bundle", "version", "openshift"];
+    let rows = ocp::matrix(catalog, package);
+    match output {
+        // JSON is rendered by show_json before getting here
+        ShowOutput::Text | ShowOutput::Table | ShowOutput::Json => {
+            println!("{}", output::table(&headers, &rows))
+        }
+        ShowOutput::Csv => {
+            println!("{}", output::csv_row(&headers));
+            for row in &rows {
+                println!("{}", output::csv_row(row));
+            }
+        }
+    }
+}
+
+fn print_annotations(package: &str, keys: &[String], catalog: &Catalog, output: ShowOutput) {
+    let matrix = annotations::matrix(catalog, package, keys);
+    let (headers, rows) = matrix.table();
+    match output {
+        // JSON is rendered by show_handler before getting here
+        ShowOutput::Text | ShowOutput::Table | ShowOutput::Json => {
+            println!("{}", output::table(&headers, &rows));
+            let changed = matrix
+                .rows
+                .iter()
+                .any(|row| row.values.iter().any(|cell| cell.changed));
+            if changed {
+                println!("* changed from the previous version");
+            }
+        }
+        ShowOutput::Csv => {
+            println!("{}", output::csv_row(&headers));
+            for row in &rows {
+                println!("{}", output::csv_row(row));
+            }
+        }
+    }
+}
+
+/// Channels of a package with their heads, the default channel first and then
+/// the ones with the newest heads, collapsed past `max` channels
+fn print_package_channels(package: &catalog::Package, max: Option<usize>, catalog: &Catalog) {
+    let heads = |channel: &Channel| -> Vec<String> {
+        channel
+            .heads()
+            .iter()
+            .map(|head| head.name.clone())
+            .collect()
+    };
+    let newest_head = |channel: &Channel| {
+        channel
+            .heads()
+            .iter()
+            .filter_map(|head| catalog.version_of(&package.name, &head.name))
+            .max()
+    };
+
+    let mut channels: Vec<&Channel> = catalog.channels_of(&package.name).collect();
+    if channels.is_empty() {
+        return;
+    }
+    let is_default = |channel: &Channel| package.default_channel.as_deref() == Some(&channel.name);
+    channels.sort_by(|a, b| {
+        is_default(b)
+            .cmp(&is_default(a))
+            .then_with(|| newest_head(b).cmp(&newest_head(a)))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    println!("{}:", label("channels"));
+    let shown = max.unwrap_or(channels.len()).min(channels.len());
+    for channel in &channels[..shown] {
+        let heads = heads(channel);
+        let head = match heads.as_slice() {
+            [] => "no head".to_string(),
+            [head] => format!("head {}", head),
+            heads => format!("heads {}", heads.join(", ")),
+        };
+        if is_default(channel) {
+            println!("  - {} (default, {})", channel.name, head);
+        } else {
+            println!("  - {} ({})", channel.name, head);
+        }
+    }
+    if shown < channels.len() {
+        println!(
+            "  … and {} more channels (use --all-channels)",
+            channels.len() - shown
+        );
+    }
+}
+
+fn print_extra_fields(extra: &serde_yaml::Mapping) {
+    if extra.is_empty() {
+        return;
+    }
+    println!("  {}:", label("additional_fields"));
+    for (key, value) in extra {
+        let render = |value: &serde_yaml::Value| match value {
+            serde_yaml::Value::String(text) => text.clone(),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        };
+        println!(
+            "{}",
+            output::wrap(&format!("    {}: {}", render(key), render(value)))
+        );
+    }
+}
+
+fn print_channel(channel: &Channel, options: &ShowOptions) {
+    match (options.entries_only, options.ordered) {
+        (false, ordered) => {
+            if ordered {
+                println!("{}", output::wrap(&OrderedChannel(channel).to_string()));
+            } else {
+                println!("{}", output::wrap(&channel.to_string()));
+            }
+            if options.verbose {
+                print_extra_fields(&channel.extra);
+            }
+        }
+        (true, false) => {
+            for entry in &channel.entries {
+                println!("{}", entry.name);
+            }
+        }
+        // Main chain first, off-chain entries after it
+        (true, true) => {
+            let upgrade_chain = channel.upgrade_chain();
+            for entry in upgrade_chain.chain.iter().chain(&upgrade_chain.off_chain) {
+                println!("{}", entry.name);
+            }
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+struct OcpMatrixItem<'a> {
+    bundle: &'a str,
+    version: &'a str,
+    openshift: &'a str,
+}
+
+/// Catalog content a name given to `show` resolves to
+enum Shown<'a> {
+    Packages(Vec<&'a catalog::Package>),
+    Channels(Vec<&'a Channel>),
+    Bundles(Vec<&'a catalog::Bundle>),
+}
+
+impl Shown<'_> {
+    fn kind(&self) -> &'static str {
+        match self {
+            Shown::Packages(_) => "PackageList",
+            Shown::Channels(_) => "ChannelList",
+            Shown::Bundles(_) => "BundleList",
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Shown::Packages(packages) => packages.is_empty(),
+            Shown::Channels(channels) => channels.is_empty(),
+            Shown::Bundles(bundles) => bundles.is_empty(),
+        }
+    }
+}
+
+/// Every object carries its identifier in JSON output
+impl Serialize for Shown<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use id::{Identified, ObjectId};
+        match self {
+            Shown::Packages(packages) => serializer.collect_seq(
+                packages
+                    .iter()
+                    .map(|package| Identified::new(ObjectId::package(&package.name), *package)),
+            ),
+            Shown::Channels(channels) => serializer.collect_seq(channels.iter().map(|channel| {
+                Identified::new(ObjectId::channel(&channel.package, &channel.name), *channel)
+            })),
+            Shown::Bundles(bundles) => serializer.collect_seq(bundles.iter().map(|bundle| {
+                Identified::new(ObjectId::bundle(&bundle.package, &bundle.name), *bundle)
+            })),
+        }
+    }
+}
+
+impl JsonSchema for Shown<'_> {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Shown".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        use id::Identified;
+        schemars::json_schema!({
+            "anyOf": [
+                generator.subschema_for::<Vec<Identified<catalog::Package>>>(),
+                generator.subschema_for::<Vec<Identified<Channel>>>(),
+                generator.subschema_for::<Vec<Identified<catalog::Bundle>>>(),
+            ]
+        })
+    }
+}
+
+/// Bundles by their own name, so `show bundle` accepts bundle names as well
+/// as package names
+fn bundles_by_name(catalog: &Catalog) -> HashMap<&str, Vec<&catalog::Bundle>> {
+    let mut index: HashMap<&str, Vec<&catalog::Bundle>> = HashMap::new();
+    for bundle in catalog.all_bundles() {
+        index.entry(bundle.name.as_str()).or_default().push(bundle);
+    }
+    index
+}
+
+/// Object an identifier names, entries show the bundle they stand for
+fn lookup_id<'a>(id: &str, catalog: &'a Catalog) -> Result<Shown<'a>, String> {
+    use id::ObjectId;
+    Ok(match ObjectId::parse(id)? {
+        ObjectId::Package(package) => Shown::Packages(match catalog.packages.get(&package) {
+            Some(CatalogEntry::OlmPackage(package)) => vec![package],
+            _ => Vec::new(),
+        }),
+        ObjectId::Channel { package, name } => {
+            Shown::Channels(catalog.channel(&package, &name).into_iter().collect())
+        }
+        ObjectId::Entry {
+            package,
+            channel,
+            name,
+        } => Shown::Bundles(
+            catalog
+                .channel(&package, &channel)
+                .and_then(|channel| channel.entry(&name))
+                .and_then(|entry| catalog.bundle(&package, &entry.name))
+                .into_iter()
+                .collect(),
+        ),
+        ObjectId::Bundle { package, name } => {
+            Shown::Bundles(catalog.bundle(&package, &name).into_iter().collect())
+        }
+    })
+}
+
+/// Resolve a name the way `show` does, None for content types `show` doesn't support
+fn lookup<'a>(
     content_type: ContentType,
     name: &str,
-    packages: &HashMap<String, CatalogEntry>,
-    channels: &HashMap<String, Vec<CatalogEntry>>,
-    bundles: &HashMap<String, Vec<CatalogEntry>>,
-) {
-    match content_type {
-        ContentType::Package => {
-            if let Some(entry) = packages.get(name) {
-                if let CatalogEntry::OlmPackage(pkg) = entry {
-                    println!("{:#?}", pkg);
+    catalog: &'a Catalog,
+    bundles: &HashMap<&str, Vec<&'a catalog::Bundle>>,
+) -> Result<Option<Shown<'a>>, String> {
+    Ok(match content_type {
+        ContentType::Package => Some(Shown::Packages(match catalog.packages.get(name) {
+            Some(CatalogEntry::OlmPackage(package)) => vec![package],
+            _ => Vec::new(),
+        })),
+        ContentType::Channel => Some(Shown::Channels(catalog.channels_of(name).collect())),
+        ContentType::Bundle => {
+            let mut found: Vec<&catalog::Bundle> = catalog.bundles_of(name).collect();
+            if found.is_empty() {
+                found = bundles.get(name).cloned().unwrap_or_default();
+            }
+            Some(Shown::Bundles(found))
+        }
+        ContentType::Id => Some(lookup_id(name, catalog)?),
+        _ => None,
+    })
+}
+
+fn print_shown(shown: &Shown, options: &ShowOptions, catalog: &Catalog) {
+    match shown {
+        Shown::Packages(packages) => {
+            for package in packages {
+                if options.ocp_matrix {
+                    print_ocp_matrix(&package.name, catalog, options.output);
+                } else if let Some(keys) = &options.annotations {
+                    print_annotations(&package.name, keys, catalog, options.output);
+                } else {
+                    println!("{}", output::wrap(&package.to_string()));
+                    print_package_channels(package, options.max_channels, catalog);
                 }
             }
         }
-        ContentType::Channel => {
-            if let Some(entries) = channels.get(name) {
-                for entry in entries {
-                    if let CatalogEntry::OpmChannel(channel) = entry {
-                        println!("{}", channel);
-                    }
+        Shown::Channels(channels)
+            if matches!(options.output, ShowOutput::Table | ShowOutput::Csv) =>
+        {
+            let headers: Vec<&str> = options
+                .columns
+                .iter()
+                .map(|column| column.header())
+                .collect();
+            let rows = entry_table::rows(channels, &options.columns, catalog);
+            if options.output == ShowOutput::Csv {
+                println!("{}", output::csv_row(&headers));
+                for row in &rows {
+                    println!("{}", output::csv_row(row));
                 }
+            } else {
+                println!("{}", output::table(&headers, &rows));
             }
         }
-        ContentType::Bundle => {
-            if let Some(entries) = bundles.get(name) {
-                for entry in entries {
-                    if let CatalogEntry::OlmBundle(bundle) = entry {
-                        println!("{:#?}", bundle);
+        Shown::Channels(channels) => {
+            for channel in channels {
+                print_channel(channel, options);
+            }
+        }
+        Shown::Bundles(bundles) => {
+            for bundle in bundles {
+                println!("{}", output::wrap(&bundle.to_string()));
+                match bundle.created_at() {
+                    Some(created) => println!("{}: {}", label("created"), created),
+                    None => println!("{}: unknown", label("created")),
+                }
+                if let Some(advice) = advice::advise(catalog, bundle) {
+                    println!("{}", output::wrap(&advice.to_string()));
+                }
+                if options.verbose {
+                    let resolved = catalog.resolve_version(&bundle.package, &bundle.name);
+                    match resolved.version {
+                        Some(version) => {
+                            println!(
+                                "{}: {} ({})",
+                                label("resolved_version"),
+                                version,
+                                resolved.source
+                            )
+                        }
+                        None => println!("{}: {}", label("resolved_version"), resolved.source),
                     }
                 }
             }
         }
-        _ => {
-            println!("Unsupported content type");
+    }
+}
+
+fn show_handler(
+    content_type: ContentType,
+    name: &str,
+    options: &ShowOptions,
+    catalog: &Catalog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if options.output == ShowOutput::Json
+        && content_type == ContentType::Package
+        && options.ocp_matrix
+    {
+        let rows = ocp::matrix(catalog, name);
+        let items: Vec<OcpMatrixItem> = rows
+            .iter()
+            .map(|row| OcpMatrixItem {
+                bundle: &row[0],
+                version: &row[1],
+                openshift: &row[2],
+            })
+            .collect();
+        println!(
+            "{}",
+            output::json_document("OcpMatrix", output::Items { items })?
+        );
+        return Ok(());
+    }
+
+    if let (ShowOutput::Json, ContentType::Package, Some(keys)) =
+        (options.output, content_type, &options.annotations)
+    {
+        let matrix = annotations::matrix(catalog, name, keys);
+        println!("{}", output::json_document("AnnotationMatrix", matrix)?);
+        return Ok(());
+    }
+
+    let bundles = bundles_by_name(catalog);
+    let Some(shown) = lookup(content_type, name, catalog, &bundles)? else {
+        println!("Unsupported content type");
+        return Ok(());
+    };
+
+    if options.output == ShowOutput::Json {
+        println!(
+            "{}",
+            output::json_document(shown.kind(), output::Items { items: shown })?
+        );
+    } else {
+        print_shown(&shown, options, catalog);
+    }
+
+    Ok(())
+}
+
+/// One requested name of a batch show
+#[derive(Serialize, JsonSchema)]
+struct BatchItem<'a> {
+    name: &'a str,
+    found: bool,
+    items: Shown<'a>,
+}
+
+/// Read the names for `show --from-file`, one per line, `-` reads stdin
+fn read_names(path: &str) -> Result<Vec<String>, String> {
+    let content = if path == "-" {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|err| format!("Failed to read names from stdin: {}", err))?;
+        content
+    } else {
+        fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Show every name of a file in one pass over the catalog, in input order
+fn show_batch_handler(
+    content_type: ContentType,
+    path: &str,
+    options: &ShowOptions,
+    catalog: &Catalog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let names = read_names(path)?;
+    let bundles = bundles_by_name(catalog);
+
+    let mut results = Vec::new();
+    for name in &names {
+        let shown = lookup(content_type, name, catalog, &bundles)?
+            .ok_or_else(|| format!("--from-file doesn't support {:?}", content_type))?;
+        results.push(BatchItem {
+            name,
+            found: !shown.is_empty(),
+            items: shown,
+        });
+    }
+
+    if options.output == ShowOutput::Json {
+        println!(
+            "{}",
+            output::json_document("ShowResults", output::Items { items: &results })?
+        );
+    } else {
+        for result in &results {
+            print_shown(&result.items, options, catalog);
         }
     }
+
+    let mut batch = Batch::new("names");
+    for result in &results {
+        if result.found {
+            batch.succeed();
+        } else {
+            batch.fail(result.name, "not found");
+        }
+    }
+    Ok(batch.finish()?)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+#[derive(Serialize, JsonSchema)]
+struct ValidationSummary {
+    errors: usize,
+    warnings: usize,
+    info: usize,
+}
 
-    // Read the content of the catalog file
-    let content = fs::read_to_string(cli.file).expect("Failed to read the file");
+/// Findings of a severity left out by --max-findings
+#[derive(Serialize, JsonSchema)]
+struct OmittedFindings {
+    severity: validate::Severity,
+    count: usize,
+}
 
-    // Deserialize the content into a Vec<CatalogEntry>
-    let entries: Vec<CatalogEntry> = serde_yaml::Deserializer::from_str(&content)
-        .into_iter()
-        .filter_map(|doc| match CatalogEntry::deserialize(doc) {
-            Ok(entry) => Some(entry),
-            Err(err) => {
-                eprintln!("Failed to deserialize a document: {}", err);
-                None
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct ValidationReport<'a> {
+    findings: Vec<&'a validate::Finding>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    omitted: Vec<OmittedFindings>,
+    summary: ValidationSummary,
+    /// --fail-fast stopped before every rule ran
+    cut_short: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct ValidationGroups {
+    groups: Vec<validate::RuleGroup>,
+    summary: ValidationSummary,
+    /// --fail-fast stopped before every rule ran
+    cut_short: bool,
+}
+
+fn stream_extract_handler(
+    sources: &[String],
+    package: &[String],
+    exact_package: &[String],
+    limits: &loader::LoadLimits,
+    out: &str,
+    dry_run: bool,
+    verify_with_opm: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if limits.trim_names {
+        return Err(
+            "--trim-names doesn't work with --stream, which copies documents byte for byte".into(),
+        );
+    }
+    if limits.follow_image {
+        return Err(
+            "--follow-image doesn't work with --stream, which only copies documents of the files"
+                .into(),
+        );
+    }
+    let matches = |value: &str, name: &str| {
+        if select::is_pattern(value) {
+            select::glob_match(value, name)
+        } else {
+            value == name
+        }
+    };
+    let mut files = Vec::new();
+    for source in sources {
+        let (_, path) = source.split_once('=').unwrap_or((source, source));
+        files.extend(loader::source_files(path)?);
+    }
+    let seen = write::stream_extract(
+        &files,
+        |name| {
+            package.iter().any(|value| matches(value, name))
+                || exact_package.iter().any(|value| value == name)
+        },
+        limits,
+        out,
+        dry_run,
+    )?;
+    if verify_with_opm {
+        opm::verify(out)?;
+    }
+
+    let mut batch = Batch::new("packages");
+    for value in package {
+        if seen.iter().any(|name| matches(value, name)) {
+            batch.succeed();
+        } else {
+            batch.fail(value, "matched no document");
+        }
+    }
+    for value in exact_package {
+        if seen.contains(value) {
+            batch.succeed();
+        } else {
+            batch.fail(value, "matched no document");
+        }
+    }
+    Ok(batch.finish()?)
+}
+
+fn validate_handler(
+    catalog: &Catalog,
+    options: &validate::ValidateOptions,
+    list_rules: bool,
+    max_findings: Option<usize>,
+    group_by: Option<validate::GroupBy>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if list_rules {
+        for rule in validate::RULES {
+            let status = if rule.default_enabled {
+                "default"
+            } else {
+                "opt-in"
+            };
+            println!("{} ({}): {}", rule.id, status, rule.description);
+        }
+        return Ok(());
+    }
+
+    let validate::Report {
+        findings,
+        cut_short,
+    } = validate::validate(catalog, options)?;
+    let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+    let summary = ValidationSummary {
+        errors: count(validate::Severity::Error),
+        warnings: count(validate::Severity::Warning),
+        info: count(validate::Severity::Info),
+    };
+    let errors = summary.errors;
+
+    match (output, group_by) {
+        (OutputFormat::Text, group_by) => {
+            if group_by == Some(validate::GroupBy::Rule) {
+                for group in validate::group_by_rule(&findings) {
+                    println!("{}", output::wrap(&group.to_string()));
+                }
+            } else {
+                let max = max_findings.unwrap_or(validate::DEFAULT_MAX_FINDINGS);
+                let (shown, omitted) = validate::truncate(&findings, max);
+                for finding in shown {
+                    println!("{}", output::wrap(&finding.to_string()));
+                }
+                for (severity, count) in omitted {
+                    println!(
+                        "... and {} more {} (use --max-findings 0)",
+                        output::thousands(count),
+                        severity.plural()
+                    );
+                }
             }
+            println!(
+                "{} errors, {} warnings, {} info{}",
+                summary.errors,
+                summary.warnings,
+                summary.info,
+                if cut_short {
+                    " (stopped at the first error, not every rule ran)"
+                } else {
+                    ""
+                }
+            );
+        }
+        (OutputFormat::Json, Some(validate::GroupBy::Rule)) => {
+            let report = ValidationGroups {
+                groups: validate::group_by_rule(&findings),
+                summary,
+                cut_short,
+            };
+            println!("{}", output::json_document("ValidationGroups", report)?);
+        }
+        (OutputFormat::Json, None) => {
+            let (shown, omitted) = validate::truncate(&findings, max_findings.unwrap_or(0));
+            let report = ValidationReport {
+                findings: shown,
+                omitted: omitted
+                    .into_iter()
+                    .map(|(severity, count)| OmittedFindings { severity, count })
+                    .collect(),
+                summary,
+                cut_short,
+            };
+            println!("{}", output::json_document("ValidationReport", report)?);
+        }
+    }
+
+    if errors > 0 {
+        return Err(format!("validation failed with {} errors", errors).into());
+    }
+    Ok(())
+}
+
+fn explain_handler(
+    request: &explain::ExplainRequest,
+    catalog: &Catalog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let verdict = explain::explain(request, catalog)?;
+    let status = if verdict.installable() {
+        "installable"
+    } else {
+        "not installable"
+    };
+    println!(
+        "{} from {}/{}: {}",
+        request.bundle, request.package, request.channel, status
+    );
+    for reason in &verdict.reasons {
+        println!("{}", output::wrap(&format!("  - {}", reason)));
+    }
+    if !verdict.notes.is_empty() {
+        println!("{}:", label("notes"));
+        for note in &verdict.notes {
+            println!("{}", output::wrap(&format!("  - {}", note)));
+        }
+    }
+
+    // The exit code carries the verdict for automated triage
+    if !verdict.installable() {
+        warnings::exit(1);
+    }
+    Ok(())
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ApiItem<'a> {
+    #[serde(flatten)]
+    gvk: &'a gvk::Gvk,
+    providers: &'a [gvk::Provider],
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ApiConflictReport<'a> {
+    findings: &'a [validate::Finding],
+    summary: ValidationSummary,
+}
+
+fn apis_handler(
+    catalog: &Catalog,
+    conflicts: bool,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let index = gvk::GvkIndex::build(catalog);
+    if !conflicts {
+        match output {
+            OutputFormat::Text => {
+                for (gvk, providers) in &index.providers {
+                    println!("- {}", gvk);
+                    for provider in providers {
+                        println!("    {}/{}", provider.package, provider);
+                    }
+                }
+            }
+            OutputFormat::Json => {
+                let items: Vec<ApiItem> = index
+                    .providers
+                    .iter()
+                    .map(|(gvk, providers)| ApiItem { gvk, providers })
+                    .collect();
+                println!(
+                    "{}",
+                    output::json_document("ApiList", output::Items { items })?
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    let findings = gvk::conflict_report(catalog, &index);
+    let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+    let summary = ValidationSummary {
+        errors: count(validate::Severity::Error),
+        warnings: count(validate::Severity::Warning),
+        info: count(validate::Severity::Info),
+    };
+    let worst = findings.iter().map(|finding| finding.severity).max();
+
+    match output {
+        OutputFormat::Text => {
+            for finding in &findings {
+                println!("{}", output::wrap(&finding.to_string()));
+            }
+            println!(
+                "{} errors, {} warnings, {} info",
+                summary.errors, summary.warnings, summary.info
+            );
+        }
+        OutputFormat::Json => {
+            let report = ApiConflictReport {
+                findings: &findings,
+                summary,
+            };
+            println!("{}", output::json_document("ApiConflictReport", report)?);
+        }
+    }
+
+    // The exit code carries the worst tier so catalog builds can gate on it
+    match worst {
+        Some(validate::Severity::Error) => warnings::exit(2),
+        Some(validate::Severity::Warning) => warnings::exit(1),
+        _ => Ok(()),
+    }
+}
+
+fn path_handler(
+    package: &str,
+    channel: &str,
+    from: &str,
+    to: &str,
+    catalog: &Catalog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(channel) = catalog.channel(package, channel) else {
+        return Err(format!("channel '{}' not found in package '{}'", channel, package).into());
+    };
+    if channel.entry(to).is_none() {
+        return Err(format!("bundle '{}' is not in channel '{}'", to, channel.name).into());
+    }
+
+    match upgrade_path::shortest_path(catalog, channel, from, to) {
+        upgrade_path::UpgradePath::Found(hops) => {
+            println!(
+                "{} -> {} in {}/{}: {} hops",
+                from,
+                to,
+                package,
+                channel.name,
+                hops.len()
+            );
+            for (index, hop) in hops.iter().enumerate() {
+                println!(
+                    "  {}. {} -> {} ({})",
+                    index + 1,
+                    hop.from,
+                    hop.to,
+                    hop.mechanism
+                );
+            }
+        }
+        upgrade_path::UpgradePath::NoPath { nearest } => {
+            println!(
+                "no path from {} to {} in {}/{}",
+                from, to, package, channel.name
+            );
+            match nearest {
+                Some(nearest) if nearest != from => {
+                    println!("Closest bundle reachable from {}: {}", from, nearest)
+                }
+                _ => println!("Nothing in the channel upgrades from {}", from),
+            }
+            warnings::exit(1);
+        }
+    }
+    Ok(())
+}
+
+fn check_conflicts_handler(
+    against: &str,
+    catalog: &Catalog,
+    limits: &loader::LoadLimits,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // A package of the loaded catalog takes precedence over a file of the same name
+    let other_catalog;
+    let candidate: Vec<&catalog::Bundle> = if catalog.packages.contains_key(against) {
+        catalog.bundles_of(against).collect()
+    } else if std::path::Path::new(against).exists() {
+        other_catalog = loader::load_catalog(against, limits)?;
+        other_catalog.all_bundles().collect()
+    } else {
+        return Err(format!("'{}' is neither a package nor a catalog file", against).into());
+    };
+
+    let index = gvk::GvkIndex::build(catalog);
+    let conflicts = gvk::find_conflicts(&candidate, &index);
+    for conflict in &conflicts {
+        println!("- {}", conflict.gvk);
+        let list = |providers: &[gvk::Provider]| {
+            providers
+                .iter()
+                .map(|provider| provider.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut existing: Vec<&str> = conflict
+            .existing
+            .iter()
+            .map(|provider| provider.package.as_str())
+            .collect();
+        existing.dedup();
+        for package in existing {
+            let providers: Vec<gvk::Provider> = conflict
+                .existing
+                .iter()
+                .filter(|provider| provider.package == package)
+                .cloned()
+                .collect();
+            println!("    owned by {}: {}", package, list(&providers));
+        }
+        println!("    candidate: {}", list(&conflict.candidate));
+    }
+    println!("{} conflicting GVKs", conflicts.len());
+
+    if !conflicts.is_empty() {
+        warnings::exit(1);
+    }
+    Ok(())
+}
+
+fn channel_diff_handler(
+    package: &str,
+    channel: &str,
+    other_channel: &str,
+    catalog: &Catalog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let find = |name: &str| {
+        catalog
+            .channel(package, name)
+            .ok_or_else(|| format!("channel '{}' not found in package '{}'", name, package))
+    };
+    let diff = channel_diff::diff(
+        find(channel)?,
+        find(other_channel)?,
+        &format!("{}/{}", package, channel),
+        &format!("{}/{}", package, other_channel),
+    );
+    println!("{}", diff);
+    Ok(())
+}
+
+fn channel_diff_between_handler(
+    package: &str,
+    channel: &str,
+    between: &[String],
+    catalogs: &[(String, Catalog)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if between.len() != 2 {
+        return Err("--between takes exactly two catalog labels".into());
+    }
+    let find = |label: &str| {
+        let (_, catalog) = catalogs
+            .iter()
+            .find(|(candidate, _)| candidate == label)
+            .ok_or_else(|| format!("no catalog labeled '{}' was loaded", label))?;
+        catalog.channel(package, channel).ok_or_else(|| {
+            format!(
+                "channel '{}' of package '{}' not found in catalog '{}'",
+                channel, package, label
+            )
+        })
+    };
+    let diff = channel_diff::diff(
+        find(&between[0])?,
+        find(&between[1])?,
+        &format!("{}/{} ({})", package, channel, between[0]),
+        &format!("{}/{} ({})", package, channel, between[1]),
+    );
+    println!("{}", diff);
+    Ok(())
+}
+
+/// Packages listed by the overview
+const OVERVIEW_PACKAGES: usize = 10;
+
+/// Counts and the first packages, what runs when no subcommand is given
+fn overview_handler(catalog: &Catalog) {
+    let names = catalog.package_names();
+    println!(
+        "{} packages, {} channels, {} bundles",
+        names.len(),
+        catalog.all_channels().count(),
+        catalog.all_bundles().count()
+    );
+
+    let rows: Vec<Vec<&str>> = names
+        .iter()
+        .take(OVERVIEW_PACKAGES)
+        .map(|&name| {
+            let default_channel = match catalog.packages.get(name) {
+                Some(CatalogEntry::OlmPackage(package)) => package.default_channel.as_deref(),
+                _ => None,
+            };
+            vec![name, default_channel.unwrap_or("-")]
         })
         .collect();
+    println!("{}", output::table(&["package", "default channel"], &rows));
+    if names.len() > OVERVIEW_PACKAGES {
+        println!(
+            "... and {} more, see `list packages`",
+            names.len() - OVERVIEW_PACKAGES
+        );
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    pager::default_sigpipe();
+    let result = run();
+    warnings::flush();
+    pager::finish();
+    result
+}
 
-    // Organize data into a HashMap of packages
-    let mut packages: HashMap<String, CatalogEntry> = HashMap::new();
-    let mut channels: HashMap<String, Vec<CatalogEntry>> = HashMap::new();
-    let mut bundles: HashMap<String, Vec<CatalogEntry>> = HashMap::new();
+/// The command line, with the examples of a subcommand at the end of its help
+fn command() -> clap::Command {
+    let mut command = Cli::command();
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    for name in names {
+        if let Some(text) = examples::after_help(&name) {
+            command = command.mut_subcommand(name, |subcommand| subcommand.after_help(text));
+        }
+    }
+    command
+}
+
+fn parse_cli(args: impl IntoIterator<Item = String>) -> Cli {
+    command()
+        .try_get_matches_from(args)
+        .and_then(|matches| Cli::from_arg_matches(&matches))
+        .unwrap_or_else(|err| err.exit())
+}
+
+/// Schemas a command looks at, for --low-memory to drop the documents of
+/// the others unparsed. None when it needs all of them.
+fn needed_schemas(low_memory: bool, command: &Option<Commands>) -> Option<&'static [&'static str]> {
+    if !low_memory {
+        return None;
+    }
+    match command {
+        Some(Commands::List {
+            content_type,
+            output,
+            ..
+        }) => match (content_type, output) {
+            (ContentType::Packages, _) => Some(&["olm.package"]),
+            (ContentType::Channels, _) => Some(&["olm.channel"]),
+            (ContentType::Bundles, OutputFormat::Text) => Some(&["olm.bundle"]),
+            // JSON items list the channels of every bundle
+            (ContentType::Bundles, OutputFormat::Json) => Some(&["olm.bundle", "olm.channel"]),
+            _ => None,
+        },
+        _ => None,
+    }
+}
 
-    for entry in entries {
-        match &entry {
-            CatalogEntry::OlmPackage(pkg) => {
-                packages.insert(pkg.name.clone(), entry);
-                // packages.entry(pkg.name.clone()) = entry;
+/// Commands needing what --low-memory leaves out fail before loading.
+/// needed_schemas only narrows `list`, every other command gets all the
+/// documents, so the text of skipped documents and bundle sizes are all
+/// they can miss.
+fn check_low_memory(command: &Option<Commands>) -> Result<(), String> {
+    let needs = match command {
+        Some(Commands::Dump { .. }) => "dump needs the text of skipped documents",
+        Some(Commands::Stats {
+            largest: Some(_), ..
+        }) => "stats --largest needs the size of every bundle document",
+        _ => return Ok(()),
+    };
+    Err(format!(
+        "{}, which --low-memory doesn't keep. Run it without --low-memory",
+        needs
+    ))
+}
+
+/// What --low-memory kept of the catalog, the source size of the loaded
+/// documents standing in for the memory they take
+fn low_memory_report(catalog: &Catalog, files: &loader::FileSummaries) -> String {
+    let (mut documents, mut bytes, mut dropped) = (0, 0, 0);
+    for (_, summary) in files {
+        documents += summary.loaded;
+        bytes += summary.loaded_bytes;
+        dropped += summary.dropped;
+    }
+    format!(
+        "low-memory: retained {} packages, {} channels, {} bundles and {} deprecations from {} documents (~{} of source), {} documents dropped unparsed",
+        catalog.packages.len(),
+        catalog.all_channels().count(),
+        catalog.all_bundles().count(),
+        catalog.deprecations.len(),
+        documents,
+        stats::rounded_size(bytes),
+        dropped
+    )
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = parse_cli(std::env::args());
+    let config = config::Config::load()?;
+    if cli.command.is_none() {
+        if let Some(default_command) = config.default_command {
+            let args = std::env::args().chain(default_command.split_whitespace().map(String::from));
+            cli = parse_cli(args);
+        }
+    }
+    output::check_api_version(cli.api_version.as_deref())?;
+    if let Some(path) = &cli.labels {
+        labels::load(path)?;
+    }
+    output::init_width(cli.width);
+    if !cli.no_pager && config.pager != Some(false) {
+        pager::start();
+    }
+    warnings::init(cli.warnings_format);
+
+    let limits = loader::LoadLimits {
+        max_document_size: cli.max_document_size,
+        max_documents: cli.max_documents,
+        max_alias_expansion: cli.max_alias_expansion,
+        substitutions: template::Substitutions::new(
+            &cli.substitute,
+            cli.substitute_file.as_deref(),
+            cli.placeholder_policy,
+        )?,
+        trim_names: cli.trim_names,
+        follow_image: cli.follow_image,
+        low_memory: cli.low_memory,
+        schemas: needed_schemas(cli.low_memory, &cli.command),
+    };
+    if let Some(Commands::Schema { document }) = cli.command {
+        return schema_handler(document);
+    }
+    if let Some(Commands::Selftest) = cli.command {
+        if !selftest::run() {
+            warnings::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(Commands::Examples) = cli.command {
+        examples::print();
+        return Ok(());
+    }
+    if cli.file.is_empty() {
+        return Err("--file is required".into());
+    }
+    if cli.low_memory {
+        check_low_memory(&cli.command)?;
+    }
+    if let Some(Commands::Extract {
+        package,
+        exact_package,
+        out,
+        sort_entries,
+        stream,
+        dry_run,
+        verify_with_opm,
+    }) = &cli.command
+    {
+        // Only where copying the source documents gives what loading would
+        let streamed = cli.low_memory
+            && *sort_entries == write::SortEntries::Source
+            && !cli.trim_names
+            && !cli.follow_image
+            && cli.file.len() == 1
+            && limits.substitutions.is_empty()
+            && limits.substitutions.policy != template::PlaceholderPolicy::Error;
+        if *stream || streamed {
+            return stream_extract_handler(
+                &cli.file,
+                package,
+                exact_package,
+                &limits,
+                out,
+                *dry_run,
+                *verify_with_opm,
+            );
+        }
+    }
+
+    let mut catalogs = Vec::new();
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    for labeled in loader::load_labeled(&cli.file, &limits) {
+        match labeled.loaded {
+            Ok((catalog, summaries)) => {
+                let ambiguous = id::ambiguities(&catalog);
+                if !ambiguous.is_empty() {
+                    let ids: Vec<String> = ambiguous
+                        .iter()
+                        .map(|(id, count)| format!("{} ({} objects)", id, count))
+                        .collect();
+                    return Err(format!(
+                        "catalog {} has ambiguous identifiers: {}",
+                        labeled.label,
+                        ids.join(", ")
+                    )
+                    .into());
+                }
+                catalogs.push((labeled.label, catalog));
+                files.extend(summaries);
             }
-            CatalogEntry::OpmChannel(chan) => {
-                channels
-                    .entry(chan.package.clone())
-                    .or_default()
-                    .push(entry);
+            Err(err) if cli.file.len() == 1 => return Err(err.into()),
+            Err(err) => failed.push((labeled.label, err)),
+        }
+    }
+    if !failed.is_empty() {
+        if cli.strict || catalogs.is_empty() {
+            let errors: Vec<String> = failed
+                .iter()
+                .map(|(label, err)| format!("catalog {}: {}", label, err))
+                .collect();
+            return Err(errors.join("; ").into());
+        }
+        for (label, err) in &failed {
+            warnings::warn(Warning::new(
+                "catalog-load-failed",
+                format!("Leaving out catalog {}: {}", label, err),
+            ));
+        }
+    }
+
+    if cli.require_identical && catalogs.len() > 1 {
+        let labels: Vec<&str> = catalogs.iter().map(|(label, _)| label.as_str()).collect();
+        let differences = identical::differences(&catalogs);
+        if !differences.is_empty() {
+            for difference in &differences {
+                warnings::warn(Warning::new("catalog-different", difference.to_string()));
             }
-            CatalogEntry::OlmBundle(bund) => {
-                bundles.entry(bund.package.clone()).or_default().push(entry);
+            return Err(format!(
+                "{} of {} catalogs differ from {}",
+                differences.len(),
+                labels.len() - 1,
+                labels[0]
+            )
+            .into());
+        }
+        warnings::warn(Warning::new(
+            "catalogs-identical",
+            format!("Catalog content identical across {}", labels.join(", ")),
+        ));
+    }
+
+    // Commands that understand labels get the catalogs before they are merged
+    if let Some(Commands::ChannelDiff {
+        package,
+        channel,
+        between,
+        ..
+    }) = &cli.command
+    {
+        if !between.is_empty() {
+            return channel_diff_between_handler(package, channel, between, &catalogs);
+        }
+    }
+
+    let mut catalogs = catalogs.into_iter();
+    let (_, mut catalog) = catalogs.next().expect("--file is required");
+    catalog.version_scheme = cli.version_scheme;
+    for (label, other) in catalogs {
+        for replaced in catalog.merge(other) {
+            if cli.verbose || warnings::structured() {
+                warnings::warn(Warning::new(
+                    "document-replaced",
+                    format!("{} replaced by the one from {}", replaced, label),
+                ));
             }
         }
     }
+    if catalog.version_scheme == resolve::VersionScheme::Lenient {
+        let lenient = catalog
+            .all_bundles()
+            .filter(|bundle| {
+                catalog
+                    .resolve_version(&bundle.package, &bundle.name)
+                    .source
+                    == resolve::VersionSource::Lenient
+            })
+            .count();
+        if lenient > 0 {
+            warnings::warn(Warning::new(
+                "version-lenient",
+                format!(
+                    "{} bundle versions derived leniently from non-semver names",
+                    lenient
+                ),
+            ));
+        }
+    }
+
+    let Some(command) = cli.command else {
+        overview_handler(&catalog);
+        return Ok(());
+    };
 
     // Handle CLI commands
-    match cli.command {
-        Commands::List { content_type } => {
-            list_handler(content_type, &packages, &channels, &bundles)
+    match command {
+        Commands::List {
+            content_type,
+            sort,
+            output,
+        } => list_handler(content_type, sort, output, cli.verbose, &catalog)?,
+
+        Commands::Show {
+            content_type,
+            name,
+            from_file,
+            ordered,
+            entries_only,
+            ocp_matrix,
+            annotations,
+            annotation,
+            columns,
+            max_channels,
+            all_channels,
+            output,
+        } => {
+            let options = ShowOptions {
+                verbose: cli.verbose,
+                ordered,
+                entries_only,
+                ocp_matrix,
+                annotations: annotations.then(|| {
+                    if annotation.is_empty() {
+                        annotations::DEFAULT_ANNOTATIONS
+                            .iter()
+                            .map(|key| key.to_string())
+                            .collect()
+                    } else {
+                        annotation
+                    }
+                }),
+                columns: if columns.is_empty() {
+                    entry_table::DEFAULT_COLUMNS.to_vec()
+                } else {
+                    columns
+                },
+                max_channels: (!all_channels).then_some(max_channels),
+                output,
+            };
+            match (from_file, name) {
+                (Some(path), _) => show_batch_handler(content_type, &path, &options, &catalog)?,
+                (None, Some(name)) => show_handler(content_type, &name, &options, &catalog)?,
+                (None, None) => unreachable!("clap requires a name or --from-file"),
+            }
+        }
+
+        Commands::Validate {
+            enable,
+            disable,
+            rules,
+            fail_fast,
+            package,
+            exact_package,
+            list_rules,
+            max_findings,
+            group_by,
+            output,
+        } => {
+            if !package.is_empty() || !exact_package.is_empty() {
+                let packages = select::expand_packages(
+                    &package,
+                    &exact_package,
+                    &catalog.package_names(),
+                    cli.verbose,
+                )?;
+                if let Some(missing) = packages
+                    .iter()
+                    .find(|name| !catalog.package_names().contains(&name.as_str()))
+                {
+                    return Err(format!("package '{}' not found in the catalog", missing).into());
+                }
+                catalog.retain_packages(&packages);
+            }
+            let options = validate::ValidateOptions {
+                enable,
+                disable,
+                rules,
+                fail_fast,
+            };
+            validate_handler(
+                &catalog,
+                &options,
+                list_rules,
+                max_findings,
+                group_by,
+                output,
+            )?
+        }
+
+        Commands::Explain {
+            package,
+            channel,
+            bundle,
+            installed,
+            ocp_version,
+        } => {
+            let request = explain::ExplainRequest {
+                package: &package,
+                channel: &channel,
+                bundle: &bundle,
+                installed: installed.as_deref(),
+                ocp_version: ocp_version.as_deref(),
+            };
+            explain_handler(&request, &catalog)?
         }
 
-        Commands::Show { content_type, name } => {
-            show_handler(content_type, &name, &packages, &channels, &bundles)
+        Commands::CheckConflicts { against } => {
+            check_conflicts_handler(&against, &catalog, &limits)?
         }
+
+        Commands::Extract {
+            package,
+            exact_package,
+            out,
+            sort_entries,
+            dry_run,
+            verify_with_opm,
+            ..
+        } => {
+            let names = catalog.package_names();
+            let package = select::expand_packages(&package, &exact_package, &names, cli.verbose)?;
+            let mut batch = Batch::new("packages");
+            let mut selected = Vec::new();
+            for name in &package {
+                if names.contains(&name.as_str()) {
+                    selected.push(name.as_str());
+                    batch.succeed();
+                } else {
+                    batch.fail(name, "not found in the catalog");
+                }
+            }
+            if !selected.is_empty() {
+                write::write_catalog(&catalog, &selected, sort_entries, &out, dry_run)?;
+                if verify_with_opm {
+                    opm::verify(&out)?;
+                }
+            }
+            batch.finish()?
+        }
+
+        Commands::Merge {
+            with,
+            out,
+            sort_entries,
+            dry_run,
+            verify_with_opm,
+        } => {
+            let mut batch = Batch::new("files");
+            for file in &with {
+                let other = match loader::load_catalog(file, &limits) {
+                    Ok(other) => other,
+                    Err(err) => {
+                        batch.fail(file, &err);
+                        continue;
+                    }
+                };
+                batch.succeed();
+                for replaced in catalog.merge(other) {
+                    warnings::warn(
+                        Warning::new(
+                            "document-replaced",
+                            format!("{} replaced by the one from {}", replaced, file),
+                        )
+                        .in_file(file),
+                    );
+                }
+            }
+            // Nothing got merged, leave `out` as it is
+            if batch.all_failed() {
+                return batch.finish().map_err(Into::into);
+            }
+            write::write_catalog(
+                &catalog,
+                &catalog.package_names(),
+                sort_entries,
+                &out,
+                dry_run,
+            )?;
+            if verify_with_opm {
+                opm::verify(&out)?;
+            }
+            batch.finish()?
+        }
+
+        Commands::ChannelDiff {
+            package,
+            channel,
+            other_channel,
+            ..
+        } => channel_diff_handler(
+            &package,
+            &channel,
+            other_channel.as_deref().unwrap_or_default(),
+            &catalog,
+        )?,
+
+        Commands::Apis { conflicts, output } => apis_handler(&catalog, conflicts, output)?,
+
+        Commands::Path {
+            package,
+            channel,
+            from,
+            to,
+        } => path_handler(&package, &channel, &from, &to, &catalog)?,
+
+        Commands::Closure {
+            package,
+            exact_package,
+            max_depth,
+        } => {
+            let package = select::expand_packages(
+                &package,
+                &exact_package,
+                &catalog.package_names(),
+                cli.verbose,
+            )?;
+            let closure = closure::closure(&catalog, &package, max_depth)?;
+            println!("{}", output::wrap(&closure.to_string()));
+            if !closure.unsatisfied.is_empty() {
+                warnings::exit(1);
+            }
+        }
+
+        Commands::Dump { output } => match output {
+            dump::DumpFormat::Json => println!(
+                "{}",
+                output::json_document("CatalogDump", dump::dump(&catalog, &files))?
+            ),
+        },
+
+        Commands::Stats {
+            largest: Some(count),
+            output,
+            ..
+        } => {
+            let bundles = stats::largest(&files, &catalog, count);
+            match output {
+                OutputFormat::Text => println!("{}", stats::largest_table(&bundles)),
+                OutputFormat::Json => println!(
+                    "{}",
+                    output::json_document("BundleSizeList", output::Items { items: bundles })?
+                ),
+            }
+        }
+
+        Commands::Stats {
+            by_file, output, ..
+        } => {
+            let stats = stats::stats(&files, &catalog, by_file);
+            match output {
+                OutputFormat::Text => println!("{}", stats::stats_table(&stats)),
+                OutputFormat::Json => {
+                    println!("{}", output::json_document("CatalogStats", stats)?)
+                }
+            }
+        }
+
+        Commands::Graph {
+            package,
+            exact_package,
+            channel,
+            format,
+        } => {
+            let packages = select::expand_packages(
+                &package,
+                &exact_package,
+                &catalog.package_names(),
+                cli.verbose,
+            )?;
+            if let Some(missing) = packages
+                .iter()
+                .find(|package| !catalog.packages.contains_key(*package))
+            {
+                return Err(format!("package '{}' not found in the catalog", missing).into());
+            }
+            let edges = graph::edges(&catalog, &packages, channel.as_deref());
+            match format {
+                graph::GraphFormat::EdgesCsv => println!("{}", graph::edges_csv(&edges)),
+            }
+        }
+
+        Commands::Schema { .. } | Commands::Selftest | Commands::Examples => {
+            unreachable!("handled before loading")
+        }
+
+        Commands::Health { only, output } => {
+            let packages = health::health(&catalog);
+            let shown: Vec<&health::PackageHealth> = packages
+                .iter()
+                .filter(|package| only.is_none_or(|only| package.status == only))
+                .collect();
+            match output {
+                OutputFormat::Json => println!(
+                    "{}",
+                    output::json_document("PackageHealthList", output::Items { items: &shown })?
+                ),
+                OutputFormat::Text => println!(
+                    "{}",
+                    output::table(&health::HEALTH_HEADERS, &health::health_table(&shown))
+                ),
+            }
+            let failing = packages
+                .iter()
+                .filter(|package| package.status == health::Status::Fail)
+                .count();
+            if failing > 0 {
+                return Err(format!("{} packages failing health checks", failing).into());
+            }
+        }
+
+        Commands::Analyze { target } => match target {
+            AnalyzeTarget::Duplicates { threshold, output } => {
+                let pairs = duplicates::duplicates(&catalog, threshold);
+                match output {
+                    OutputFormat::Json => println!(
+                        "{}",
+                        output::json_document("DuplicateList", output::Items { items: pairs })?
+                    ),
+                    OutputFormat::Text if pairs.is_empty() => println!(
+                        "No packages share {}% or more of their bundle images",
+                        threshold
+                    ),
+                    OutputFormat::Text => {
+                        let pairs: Vec<String> =
+                            pairs.iter().map(|pair| pair.to_string()).collect();
+                        println!("{}", output::wrap(&pairs.join("\n\n")));
+                    }
+                }
+            }
+        },
+
+        Commands::Generate { target } => match target {
+            GenerateTarget::Imageset {
+                package,
+                channel,
+                version_range,
+                catalog_image,
+                out,
+                dry_run,
+            } => {
+                let request = imageset::ImagesetRequest {
+                    package: &package,
+                    channel: channel.as_deref(),
+                    version_range: version_range.as_deref(),
+                    catalog_image: &catalog_image,
+                    out: &out,
+                    dry_run,
+                };
+                imageset::generate_imageset(&request, &catalog)?
+            }
+        },
     }
 
+    if cli.low_memory {
+        warnings::warn(Warning::new(
+            "low-memory-retained",
+            low_memory_report(&catalog, &files),
+        ));
+    }
     Ok(())
 }
@@ -0,0 +1,144 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Object {
+    api_version: String,
+    kind: String,
+    #[serde(default)]
+    metadata: Metadata,
+    #[serde(default)]
+    data: BTreeMap<String, String>,
+    #[serde(default)]
+    spec: CatalogSourceSpec,
+}
+
+#[derive(Deserialize, Default)]
+struct Metadata {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct CatalogSourceSpec {
+    source_type: Option<String>,
+    image: Option<String>,
+}
+
+/// Kubernetes object a catalog can come wrapped in, e.g. in must-gather archives
+pub enum Manifest {
+    /// Catalog documents inline in the data keys of a ConfigMap, as served by
+    /// the legacy configmap-server, or in annotations of any object, like the
+    /// rendered catalog support bundles attach. Keys of annotations are
+    /// prefixed with `annotations/`.
+    Inline {
+        name: String,
+        data: BTreeMap<String, String>,
+    },
+    /// grpc CatalogSource, the catalog itself lives in the image
+    CatalogSource { name: String, image: String },
+}
+
+/// Recognize a document that isn't a catalog document but a Kubernetes
+/// object carrying or pointing at one
+pub fn parse(content: &str) -> Option<Manifest> {
+    let object: Object = serde_yaml::from_str(content).ok()?;
+    if object.api_version.is_empty() {
+        return None;
+    }
+    let mut data = if object.kind == "ConfigMap" {
+        object.data
+    } else {
+        BTreeMap::new()
+    };
+    data.extend(
+        object
+            .metadata
+            .annotations
+            .into_iter()
+            .filter(|(_, value)| holds_catalog(value))
+            .map(|(key, value)| (format!("annotations/{}", key), value)),
+    );
+    let name = object.metadata.name;
+    match object.kind.as_str() {
+        "ConfigMap" => Some(Manifest::Inline { name, data }),
+        _ if !data.is_empty() => Some(Manifest::Inline { name, data }),
+        "CatalogSource" if object.spec.source_type.as_deref() == Some("grpc") => {
+            Some(Manifest::CatalogSource {
+                name,
+                image: object.spec.image?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether an annotation value looks like catalog documents, going by a
+/// top-level or JSON `schema` of an OLM one, so that other annotations
+/// aren't loaded and reported as invalid documents
+fn holds_catalog(value: &str) -> bool {
+    value.lines().any(|line| {
+        line.starts_with("schema: olm.")
+            || line.contains("\"schema\": \"olm.")
+            || line.contains("\"schema\":\"olm.")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RENDERED: &str = "schema: olm.package\nname: demo\n";
+
+    fn inline(content: &str) -> Vec<String> {
+        match parse(content) {
+            Some(Manifest::Inline { data, .. }) => data.into_keys().collect(),
+            _ => panic!("not inline documents: {}", content),
+        }
+    }
+
+    #[test]
+    fn annotations_holding_catalogs_are_inline_documents() {
+        let object = |kind: &str, extra: &str| {
+            format!(
+                "apiVersion: v1\nkind: {}\nmetadata:\n  name: x\n  annotations:\n    example.com/fbc: {:?}\n    example.com/owner: team\n{}",
+                kind, RENDERED, extra
+            )
+        };
+        assert_eq!(
+            inline(&object(
+                "ConfigMap",
+                "data:\n  catalog.yaml: |\n    schema: olm.package\n"
+            )),
+            ["annotations/example.com/fbc", "catalog.yaml"]
+        );
+        assert_eq!(inline(&object("Pod", "")), ["annotations/example.com/fbc"]);
+        // The rendered catalog wins over the image it was rendered from
+        assert_eq!(
+            inline(&object(
+                "CatalogSource",
+                "spec:\n  sourceType: grpc\n  image: quay.io/x/index:1\n"
+            )),
+            ["annotations/example.com/fbc"]
+        );
+    }
+
+    #[test]
+    fn objects_without_catalogs_point_at_their_image_or_nothing() {
+        let catalog_source = "apiVersion: operators.coreos.com/v1alpha1\nkind: CatalogSource\nmetadata:\n  name: x\nspec:\n  sourceType: grpc\n  image: quay.io/x/index:1\n";
+        match parse(catalog_source) {
+            Some(Manifest::CatalogSource { name, image }) => {
+                assert_eq!((name.as_str(), image.as_str()), ("x", "quay.io/x/index:1"))
+            }
+            _ => panic!("not a CatalogSource"),
+        }
+        assert!(
+            parse("apiVersion: v1\nkind: Pod\nmetadata:\n  annotations:\n    a: b\n").is_none()
+        );
+        assert!(parse("schema: olm.package\nname: demo\n").is_none());
+    }
+}
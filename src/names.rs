@@ -0,0 +1,158 @@
+use std::fmt;
+
+use crate::catalog::{Catalog, CatalogEntry};
+
+/// Name, package, replaces or skips value that can't match anything as
+/// written, usually pasted from a spreadsheet
+#[derive(Debug)]
+pub struct UntidyName {
+    /// Document holding the value, `package/name` like validate locations
+    pub location: String,
+    /// Field of the document, e.g. `entries[2].replaces`
+    pub field: String,
+    pub value: String,
+}
+
+impl fmt::Display for UntidyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = if self.value.trim().chars().any(char::is_control) {
+            "control characters in it"
+        } else {
+            "whitespace around it"
+        };
+        write!(f, "{} {:?} has {}", self.field, self.value, problem)
+    }
+}
+
+fn is_untidy(value: &str) -> bool {
+    value.trim() != value || value.chars().any(char::is_control)
+}
+
+/// The value without control characters and surrounding whitespace
+fn tidy(value: &str) -> String {
+    let kept: String = value.chars().filter(|c| !c.is_control()).collect();
+    kept.trim().to_string()
+}
+
+/// Name field of a document
+#[derive(Clone, Copy)]
+enum Field {
+    Name,
+    Package,
+    EntryName(usize),
+    EntryReplaces(usize),
+    EntrySkip(usize, usize),
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Name => write!(f, "name"),
+            Field::Package => write!(f, "package"),
+            Field::EntryName(index) => write!(f, "entries[{}].name", index),
+            Field::EntryReplaces(index) => write!(f, "entries[{}].replaces", index),
+            Field::EntrySkip(index, skip) => write!(f, "entries[{}].skips[{}]", index, skip),
+        }
+    }
+}
+
+fn name_fields(entry: &CatalogEntry) -> Vec<(Field, &str)> {
+    match entry {
+        CatalogEntry::OlmPackage(package) => vec![(Field::Name, &package.name)],
+        CatalogEntry::OpmChannel(channel) => {
+            let mut fields = vec![
+                (Field::Name, &*channel.name),
+                (Field::Package, &channel.package),
+            ];
+            for (index, entry) in channel.entries.iter().enumerate() {
+                fields.push((Field::EntryName(index), &entry.name));
+                fields.push((Field::EntryReplaces(index), &entry.replaces));
+                for (skip, name) in entry.skips.iter().enumerate() {
+                    fields.push((Field::EntrySkip(index, skip), name));
+                }
+            }
+            fields
+        }
+        CatalogEntry::OlmBundle(bundle) => {
+            vec![
+                (Field::Name, &bundle.name),
+                (Field::Package, &bundle.package),
+            ]
+        }
+        CatalogEntry::OlmDeprecations(deprecations) => {
+            vec![(Field::Package, &deprecations.package)]
+        }
+    }
+}
+
+fn location(entry: &CatalogEntry) -> String {
+    match entry {
+        CatalogEntry::OlmPackage(package) => package.name.clone(),
+        CatalogEntry::OpmChannel(channel) => format!("{}/{}", channel.package, channel.name),
+        CatalogEntry::OlmBundle(bundle) => format!("{}/{}", bundle.package, bundle.name),
+        CatalogEntry::OlmDeprecations(deprecations) => deprecations.package.clone(),
+    }
+}
+
+/// Untidy name fields of a document
+pub fn check(entry: &CatalogEntry) -> Vec<UntidyName> {
+    let fields = name_fields(entry);
+    if fields.iter().all(|(_, value)| !is_untidy(value)) {
+        return Vec::new();
+    }
+    let location = location(entry);
+    fields
+        .into_iter()
+        .filter(|(_, value)| is_untidy(value))
+        .map(|(field, value)| UntidyName {
+            location: location.clone(),
+            field: field.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+}
+
+/// Tidy the name fields of a document in place, for --trim-names
+pub fn trim(entry: &mut CatalogEntry) {
+    let fix = |value: &mut String| {
+        if is_untidy(value) {
+            *value = tidy(value);
+        }
+    };
+    match entry {
+        CatalogEntry::OlmPackage(package) => fix(&mut package.name),
+        CatalogEntry::OpmChannel(channel) => {
+            fix(&mut channel.name);
+            fix(&mut channel.package);
+            for entry in &mut channel.entries {
+                fix(&mut entry.name);
+                fix(&mut entry.replaces);
+                entry.skips.iter_mut().for_each(fix);
+            }
+        }
+        CatalogEntry::OlmBundle(bundle) => {
+            fix(&mut bundle.name);
+            fix(&mut bundle.package);
+        }
+        CatalogEntry::OlmDeprecations(deprecations) => fix(&mut deprecations.package),
+    }
+}
+
+fn documents_of<'a>(catalog: &'a Catalog, package: &str) -> impl Iterator<Item = &'a CatalogEntry> {
+    catalog
+        .packages
+        .get(package)
+        .into_iter()
+        .chain(catalog.channels.get(package).into_iter().flatten())
+        .chain(catalog.bundles.get(package).into_iter().flatten())
+        .chain(catalog.deprecations.get(package))
+}
+
+/// Untidy name fields of the documents of the given packages
+pub fn untidy(catalog: &Catalog, packages: &[&str]) -> Vec<UntidyName> {
+    packages
+        .iter()
+        .flat_map(|package| documents_of(catalog, package))
+        .flat_map(check)
+        .collect()
+}
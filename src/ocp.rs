@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::catalog::{Bundle, Catalog};
+use crate::version::parse_major_minor;
+
+type OcpVersion = (u64, u64);
+
+/// OpenShift versions a bundle can be installed on, bounds are inclusive
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct OcpRange {
+    pub min: Option<OcpVersion>,
+    pub max: Option<OcpVersion>,
+}
+
+impl fmt::Display for OcpRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = |(major, minor): OcpVersion| format!("{}.{}", major, minor);
+        match (self.min, self.max) {
+            (None, None) => write!(f, "all"),
+            (Some(min), None) => write!(f, ">={}", version(min)),
+            (None, Some(max)) => write!(f, "<={}", version(max)),
+            (Some(min), Some(max)) if min == max => write!(f, "{}", version(min)),
+            (Some(min), Some(max)) => write!(f, "{}-{}", version(min), version(max)),
+        }
+    }
+}
+
+/// Parse the `com.redhat.openshift.versions` syntax: `v4.12` (that version
+/// and later), `=v4.12` (only that version), `v4.12-v4.15` (a range) or the
+/// legacy comma separated list `v4.5,v4.6`
+pub fn parse_openshift_versions(input: &str) -> Option<OcpRange> {
+    let input = input.trim();
+    if let Some(exact) = input.strip_prefix('=') {
+        let version = parse_major_minor(exact)?;
+        return Some(OcpRange {
+            min: Some(version),
+            max: Some(version),
+        });
+    }
+    if let Some((min, max)) = input.split_once('-') {
+        return Some(OcpRange {
+            min: Some(parse_major_minor(min)?),
+            max: Some(parse_major_minor(max)?),
+        });
+    }
+    if input.contains(',') {
+        let versions = input
+            .split(',')
+            .map(parse_major_minor)
+            .collect::<Option<Vec<_>>>()?;
+        return Some(OcpRange {
+            min: versions.iter().min().copied(),
+            // The legacy list format means the listed versions and later ones
+            max: None,
+        });
+    }
+    Some(OcpRange {
+        min: Some(parse_major_minor(input)?),
+        max: None,
+    })
+}
+
+/// Combine olm.maxOpenShiftVersion with `com.redhat.openshift.versions`,
+/// taken either from a property of that type or from the CSV annotations
+pub fn bundle_ocp_range(bundle: &Bundle) -> OcpRange {
+    let mut range = bundle
+        .property("com.redhat.openshift.versions")
+        .and_then(|property| property.value.as_str())
+        .or_else(|| bundle.csv_annotation("com.redhat.openshift.versions"))
+        .and_then(parse_openshift_versions)
+        .unwrap_or_default();
+
+    if let Some(max) = bundle
+        .max_openshift_version()
+        .as_deref()
+        .and_then(parse_major_minor)
+    {
+        range.max = Some(range.max.map_or(max, |current| current.min(max)));
+    }
+    range
+}
+
+/// Bundle name, version and supported OpenShift range of every bundle of a
+/// package, ordered by version
+pub fn matrix(catalog: &Catalog, package: &str) -> Vec<Vec<String>> {
+    let mut bundles: Vec<&Bundle> = catalog.bundles_of(package).collect();
+    let version_of = |bundle: &Bundle| catalog.version_of(&bundle.package, &bundle.name);
+    bundles.sort_by(|a, b| (version_of(a), &a.name).cmp(&(version_of(b), &b.name)));
+    bundles
+        .into_iter()
+        .map(|bundle| {
+            vec![
+                bundle.name.clone(),
+                version_of(bundle)
+                    .map(|version| version.to_string())
+                    .unwrap_or_default(),
+                bundle_ocp_range(bundle).to_string(),
+            ]
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::warnings::{self, Warning};
+
+/// Lines of opm's output are prefixed with this on stderr
+const PREFIX: &str = "opm: ";
+
+fn find_opm() -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join("opm"))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Run `opm validate` on a written catalog, failing when opm rejects it.
+/// Skipped with a warning when opm isn't on PATH.
+pub fn verify(out: &str) -> Result<(), String> {
+    let Some(opm) = find_opm() else {
+        warnings::warn(Warning::new(
+            "opm-missing",
+            format!("opm not found on PATH, not verifying {}", out),
+        ));
+        return Ok(());
+    };
+
+    // opm validates directories, a single file gets one of its own
+    let written = Path::new(out);
+    let scratch = (!written.is_dir())
+        .then(|| env::temp_dir().join(format!("catalog-browser-verify-{}", std::process::id())));
+    if let Some(scratch) = &scratch {
+        let name = written.file_name().unwrap_or("catalog.yaml".as_ref());
+        fs::create_dir_all(scratch)
+            .and_then(|_| fs::copy(written, scratch.join(name)))
+            .map_err(|err| format!("Failed to stage {} for opm: {}", out, err))?;
+    }
+    let result = Command::new(&opm)
+        .arg("validate")
+        .arg(scratch.as_deref().unwrap_or(written))
+        .output();
+    if let Some(scratch) = &scratch {
+        let _ = fs::remove_dir_all(scratch);
+    }
+    let output = result.map_err(|err| format!("Failed to run {}: {}", opm.display(), err))?;
+
+    for stream in [&output.stdout, &output.stderr] {
+        for line in String::from_utf8_lossy(stream).lines() {
+            warnings::warn(Warning::new("opm-output", format!("{}{}", PREFIX, line)));
+        }
+    }
+    if !output.status.success() {
+        return Err(format!("opm validate rejected {} ({})", out, output.status));
+    }
+    warnings::warn(Warning::new(
+        "opm-accepted",
+        format!("opm validate accepted {}", out),
+    ));
+    Ok(())
+}
+
+/// Render a catalog image to its documents with `opm render`, for
+/// --follow-image. opm's stderr is passed on as warnings.
+pub fn render(image: &str) -> Result<Vec<u8>, String> {
+    let opm = find_opm()
+        .ok_or_else(|| format!("opm not found on PATH, it's needed to render {}", image))?;
+    let output = Command::new(&opm)
+        .args(["render", image, "--output", "yaml"])
+        .output()
+        .map_err(|err| format!("Failed to run {}: {}", opm.display(), err))?;
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        warnings::warn(Warning::new("opm-output", format!("{}{}", PREFIX, line)));
+    }
+    if !output.status.success() {
+        return Err(format!(
+            "opm render failed for {} ({})",
+            image, output.status
+        ));
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,240 @@
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema};
+use serde::Serialize;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+
+use crate::labels;
+
+/// Version of the JSON output shapes, only bumped on breaking changes
+pub const API_VERSION: &str = "catalog-browser/v1";
+pub const SUPPORTED_API_VERSIONS: &[&str] = &[API_VERSION];
+
+/// Envelope of every JSON document the tool prints
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct Document<'a, T> {
+    api_version: &'static str,
+    kind: &'a str,
+    #[serde(flatten)]
+    body: T,
+}
+
+/// Body of list-like documents, `items` serializes as an array
+#[derive(Serialize, JsonSchema)]
+pub struct Items<T> {
+    pub items: T,
+}
+
+pub fn json_document<T: Serialize>(kind: &str, body: T) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&Document {
+        api_version: API_VERSION,
+        kind,
+        body,
+    })
+}
+
+/// JSON Schema of the document json_document prints for a body of type `T`
+pub fn json_schema<T: JsonSchema>(kind: &str) -> Schema {
+    // Documents are only ever printed, fields skipped when empty aren't required
+    let mut schema = SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<Document<T>>();
+    schema.insert("title".into(), kind.into());
+    if let Some(properties) = schema
+        .get_mut("properties")
+        .and_then(|properties| properties.as_object_mut())
+    {
+        properties.insert(
+            "apiVersion".into(),
+            serde_json::json!({ "const": API_VERSION }),
+        );
+        properties.insert("kind".into(), serde_json::json!({ "const": kind }));
+    }
+    schema
+}
+
+/// Fail when a consumer pins a JSON API version this build doesn't produce
+pub fn check_api_version(requested: Option<&str>) -> Result<(), String> {
+    match requested {
+        Some(version) if !SUPPORTED_API_VERSIONS.contains(&version) => Err(format!(
+            "unsupported --api-version '{}', supported: {}",
+            version,
+            SUPPORTED_API_VERSIONS.join(", ")
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Number with thousands separators, e.g. `9,412`
+pub fn thousands(number: usize) -> String {
+    let digits = number.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn csv_row<S: AsRef<str>>(fields: &[S]) -> String {
+    fields
+        .iter()
+        .map(|field| csv_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Width used when stdout is a terminal but COLUMNS isn't set
+const DEFAULT_WIDTH: usize = 80;
+/// Table columns are never truncated below this
+const MIN_COLUMN_WIDTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Width {
+    /// Columns to wrap text at, None to never wrap
+    columns: Option<usize>,
+    /// Whether table cells may be cut, only done on terminals
+    truncate: bool,
+}
+
+static WIDTH: OnceLock<Width> = OnceLock::new();
+
+/// Work out the output width once, `--width` wins over the terminal's.
+/// Redirected output is only wrapped when asked for and never truncated.
+pub fn init_width(requested: Option<usize>) {
+    let tty = io::stdout().is_terminal();
+    let columns = requested.or_else(|| {
+        tty.then(|| {
+            env::var("COLUMNS")
+                .ok()
+                .and_then(|columns| columns.trim().parse().ok())
+                .filter(|&columns| columns > 0)
+                .unwrap_or(DEFAULT_WIDTH)
+        })
+    });
+    let _ = WIDTH.set(Width {
+        columns,
+        truncate: tty,
+    });
+}
+
+fn width() -> Width {
+    WIDTH.get().copied().unwrap_or_default()
+}
+
+/// Wrap every line longer than the output width at whitespace, continuation
+/// lines are indented two spaces past the line they belong to. Words longer
+/// than the width, like image references, are never split.
+pub fn wrap(text: &str) -> String {
+    let Some(columns) = width().columns else {
+        return text.to_string();
+    };
+
+    let mut wrapped = Vec::new();
+    for line in text.lines() {
+        if line.chars().count() <= columns {
+            wrapped.push(line.to_string());
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let continuation = " ".repeat(indent + 2);
+        let mut current = line[..indent].to_string();
+        let mut current_len = indent;
+        let mut has_word = false;
+        for word in line.split_whitespace() {
+            let word_len = word.chars().count();
+            if has_word && current_len + 1 + word_len > columns {
+                wrapped.push(current);
+                current = continuation.clone();
+                current_len = continuation.len();
+                has_word = false;
+            }
+            if has_word {
+                current.push(' ');
+                current_len += 1;
+            }
+            current.push_str(word);
+            current_len += word_len;
+            has_word = true;
+        }
+        wrapped.push(current);
+    }
+    wrapped.join("\n")
+}
+
+fn truncate(cell: &str, width: usize) -> String {
+    if cell.chars().count() <= width {
+        return cell.to_string();
+    }
+    let mut truncated: String = cell.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Render rows as left-aligned columns separated by two spaces, cutting the
+/// widest columns when a terminal is too narrow. Headers go through the
+/// label table.
+pub fn table<S: AsRef<str>>(headers: &[&str], rows: &[Vec<S>]) -> String {
+    let headers: Vec<&str> = headers
+        .iter()
+        .map(|header| labels::header(header))
+        .collect();
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.as_ref().chars().count());
+        }
+    }
+
+    if let Width {
+        columns: Some(columns),
+        truncate: true,
+    } = width()
+    {
+        let separators = 2 * widths.len().saturating_sub(1);
+        while widths.iter().sum::<usize>() + separators > columns {
+            let Some(widest) = widths
+                .iter_mut()
+                .filter(|width| **width > MIN_COLUMN_WIDTH)
+                .max_by_key(|width| **width)
+            else {
+                break;
+            };
+            *widest -= 1;
+        }
+    }
+
+    let render = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:width$}", truncate(cell, width), width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![render(headers)];
+    for row in rows {
+        lines.push(render(row.iter().map(|cell| cell.as_ref()).collect()));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,98 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+/// Running pager with the descriptors stdout and stderr had before it took them over
+struct Pager {
+    child: Child,
+    stdout: i32,
+    stderr: Option<i32>,
+}
+
+static PAGER: Mutex<Option<Pager>> = Mutex::new(None);
+
+/// Send the rest of the output through `$PAGER`, `less -FRX` by default,
+/// when stdout is a terminal. `less -F` quits right away on output that
+/// fits the screen. A missing pager is no error, output then goes straight
+/// to the terminal.
+#[cfg(unix)]
+pub fn start() {
+    if !io::stdout().is_terminal() {
+        return;
+    }
+    let command = env::var("PAGER").unwrap_or_else(|_| "less -FRX".to_string());
+    let mut words = command.split_whitespace();
+    let Some(program) = words.next() else {
+        return;
+    };
+    if program == "cat" {
+        return;
+    }
+    let Ok(mut child) = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return;
+    };
+    let Some(stdin) = child.stdin.take() else {
+        return;
+    };
+
+    use std::os::fd::AsRawFd;
+    let pipe = stdin.as_raw_fd();
+    // SAFETY: plain descriptor juggling, the duplicates stay open until finish()
+    let pager = unsafe {
+        let stdout = libc::dup(1);
+        libc::dup2(pipe, 1);
+        let stderr = io::stderr().is_terminal().then(|| {
+            let stderr = libc::dup(2);
+            libc::dup2(pipe, 2);
+            stderr
+        });
+        Pager {
+            child,
+            stdout,
+            stderr,
+        }
+    };
+    *PAGER.lock().unwrap() = Some(pager);
+}
+
+#[cfg(not(unix))]
+pub fn start() {}
+
+/// Let a closed stdout end the process quietly, the way a pager quit early
+/// or `| head` expects, instead of failing the next print. Rust ignores
+/// SIGPIPE by default.
+#[cfg(unix)]
+pub fn default_sigpipe() {
+    // SAFETY: restores the default disposition before any output happens
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn default_sigpipe() {}
+
+/// Hand stdout and stderr back to the terminal and wait for the pager to
+/// quit, which it does once it has read everything
+pub fn finish() {
+    let Some(mut pager) = PAGER.lock().unwrap().take() else {
+        return;
+    };
+    let _ = io::stdout().flush();
+    #[cfg(unix)]
+    // SAFETY: restores the descriptors duplicated in start()
+    unsafe {
+        libc::dup2(pager.stdout, 1);
+        libc::close(pager.stdout);
+        if let Some(stderr) = pager.stderr {
+            libc::dup2(stderr, 2);
+            libc::close(stderr);
+        }
+    }
+    let _ = pager.child.wait();
+}
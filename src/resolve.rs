@@ -0,0 +1,112 @@
+use std::fmt;
+
+use crate::catalog::{version_from_name, Bundle};
+use crate::version::Version;
+
+/// Which version resolution strategies may be used
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Debug, Default)]
+pub enum VersionScheme {
+    /// olm.package property or a semver `.vX.Y.Z` name suffix only
+    #[default]
+    Strict,
+    /// Also derive sortable versions from other numeric name patterns
+    Lenient,
+}
+
+/// Strategy a version was resolved with, in the order they are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionSource {
+    Property,
+    Name,
+    Lenient,
+    Unversioned,
+}
+
+impl VersionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionSource::Property => "property",
+            VersionSource::Name => "name",
+            VersionSource::Lenient => "lenient",
+            VersionSource::Unversioned => "unversioned",
+        }
+    }
+}
+
+impl fmt::Display for VersionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedVersion {
+    pub version: Option<Version>,
+    pub source: VersionSource,
+}
+
+/// Extract a sortable version from names like `foo.v2024.03.1-2` or
+/// `foo-operator.1.2`: the numbers following the first digit that starts a
+/// new name segment become major, minor and patch, anything after them is
+/// kept as build metadata so it only breaks ties
+pub fn lenient_version(name: &str) -> Option<Version> {
+    let chars: Vec<char> = name.chars().collect();
+    let starts_segment = |index: usize| {
+        let separator = |c: char| !c.is_ascii_alphanumeric();
+        match index {
+            0 => true,
+            1 => separator(chars[0]) || chars[0] == 'v',
+            _ => {
+                separator(chars[index - 1])
+                    || (chars[index - 1] == 'v' && separator(chars[index - 2]))
+            }
+        }
+    };
+    let start =
+        (0..chars.len()).find(|&index| chars[index].is_ascii_digit() && starts_segment(index))?;
+
+    let suffix: String = chars[start..].iter().collect();
+    let numbers: Vec<u64> = suffix
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let number = |index: usize| numbers.get(index).copied().unwrap_or(0);
+
+    Some(Version {
+        major: number(0),
+        minor: number(1),
+        patch: number(2),
+        pre: Vec::new(),
+        build: numbers
+            .iter()
+            .skip(3)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("."),
+    })
+}
+
+/// Resolve the version of a channel entry or bundle. `bundle` is the bundle
+/// document when one exists, otherwise only the name is available.
+pub fn resolve(bundle: Option<&Bundle>, name: &str, scheme: VersionScheme) -> ResolvedVersion {
+    let resolved = |version, source| ResolvedVersion {
+        version: Some(version),
+        source,
+    };
+    if let Some(version) = bundle.and_then(|bundle| bundle.version()) {
+        return resolved(version, VersionSource::Property);
+    }
+    if let Some(version) = version_from_name(name) {
+        return resolved(version, VersionSource::Name);
+    }
+    if scheme == VersionScheme::Lenient {
+        if let Some(version) = lenient_version(name) {
+            return resolved(version, VersionSource::Lenient);
+        }
+    }
+    ResolvedVersion {
+        version: None,
+        source: VersionSource::Unversioned,
+    }
+}
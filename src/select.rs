@@ -0,0 +1,77 @@
+use crate::warnings::{self, Warning};
+
+/// Whether a --package value is a glob pattern rather than a package name
+pub fn is_pattern(value: &str) -> bool {
+    value.contains(['*', '?'])
+}
+
+/// Match a name against a glob pattern, `*` matching any run of characters
+/// and `?` any single one
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Position of the last `*` and of the name character it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Expand --package values against the package names of the catalog. Plain
+/// names and --exact-package values are kept as they are so the caller
+/// reports the missing ones, a pattern matching nothing is an error.
+pub fn expand_packages(
+    values: &[String],
+    exact: &[String],
+    names: &[&str],
+    verbose: bool,
+) -> Result<Vec<String>, String> {
+    let mut packages: Vec<String> = Vec::new();
+    let mut add = |package: &str| {
+        if !packages.iter().any(|added| added == package) {
+            packages.push(package.to_string());
+        }
+    };
+    for value in values {
+        if !is_pattern(value) {
+            add(value);
+            continue;
+        }
+        let matches: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|name| glob_match(value, name))
+            .collect();
+        if matches.is_empty() {
+            return Err(format!("--package '{}' matches no package", value));
+        }
+        if verbose {
+            warnings::warn(Warning::new(
+                "package-pattern-matches",
+                format!("--package '{}' matches {}", value, matches.join(", ")),
+            ));
+        }
+        matches.into_iter().for_each(&mut add);
+    }
+    exact.iter().for_each(|package| add(package));
+    Ok(packages)
+}
@@ -0,0 +1,495 @@
+use std::env;
+use std::fs;
+use std::process::{self, Command};
+
+use crate::catalog::{Catalog, CatalogEntry};
+use crate::examples::EXAMPLES;
+use crate::id::ObjectId;
+use crate::loader::{self, CatalogReader, Limit, LoadError, LoadLimits, LoadSummary};
+use crate::template::{PlaceholderPolicy, Substitutions};
+use crate::upgrade_path::{self, Mechanism, UpgradePath};
+use crate::validate::{self, Severity, ValidateOptions};
+use crate::{closure, dump, graph, identical, stats, warnings, write};
+
+const CATALOG_YAML: &str = include_str!("selftest/catalog.yaml");
+const CATALOG_JSON: &str = include_str!("selftest/catalog.json");
+const DEPRECATIONS: &str = include_str!("selftest/deprecations.yaml");
+const TEMPLATE: &str = include_str!("selftest/template.yaml");
+const BROKEN: &str = include_str!("selftest/broken.yaml");
+const CROSS_REPLACES: &str = include_str!("selftest/cross-replaces.yaml");
+const UNTIDY: &str = include_str!("selftest/untidy.yaml");
+const PREVIOUS: &str = include_str!("selftest/previous.yaml");
+
+struct Scenario {
+    name: &'static str,
+    description: &'static str,
+    run: fn() -> Result<(), String>,
+}
+
+const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "yaml-fbc",
+        description: "YAML catalog through the loader and the major commands",
+        run: yaml_fbc,
+    },
+    Scenario {
+        name: "json-fbc",
+        description: "opm-style JSON catalog loads the same as its YAML twin",
+        run: json_fbc,
+    },
+    Scenario {
+        name: "deprecations",
+        description: "olm.deprecations messages for packages and bundles",
+        run: deprecations,
+    },
+    Scenario {
+        name: "templates",
+        description: "placeholders with --substitute values and without",
+        run: templates,
+    },
+    Scenario {
+        name: "broken",
+        description: "invalid documents are skipped, the rest still loads",
+        run: broken,
+    },
+    Scenario {
+        name: "cross-package-replaces",
+        description: "replaces targets found only in another package",
+        run: cross_package_replaces,
+    },
+    Scenario {
+        name: "identifiers",
+        description: "object identifiers round-trip and malformed ones are rejected",
+        run: identifiers,
+    },
+    Scenario {
+        name: "catalog-reader",
+        description: "document iterator with errors in between, dropped early",
+        run: catalog_reader,
+    },
+    Scenario {
+        name: "untidy-names",
+        description: "names with whitespace or control characters, reported and trimmed",
+        run: untidy_names,
+    },
+    Scenario {
+        name: "low-memory",
+        description: "documents of unneeded schemas dropped unparsed, skipped text not kept",
+        run: low_memory,
+    },
+    Scenario {
+        name: "examples",
+        description: "every invocation printed by `examples` runs and answers its question",
+        run: examples,
+    },
+];
+
+fn ensure(condition: bool, failure: &str) -> Result<(), String> {
+    if condition {
+        Ok(())
+    } else {
+        Err(failure.to_string())
+    }
+}
+
+fn limits(substitutions: Substitutions) -> LoadLimits {
+    LoadLimits {
+        substitutions,
+        ..LoadLimits::default()
+    }
+}
+
+fn no_substitutions() -> Substitutions {
+    Substitutions::new(&[], None, PlaceholderPolicy::Warn).expect("nothing to parse")
+}
+
+fn load(content: &str, label: &str, limits: &LoadLimits) -> Result<(Catalog, LoadSummary), String> {
+    let (entries, summary) = loader::load_str(content, label, limits)
+        .map_err(|err| format!("failed to load: {}", err))?;
+    Ok((Catalog::from_entries(entries), summary))
+}
+
+fn yaml_fbc() -> Result<(), String> {
+    let limits = limits(no_substitutions());
+    let (catalog, summary) = load(CATALOG_YAML, "catalog.yaml", &limits)?;
+    ensure(summary.failed == 0, "documents failed to parse")?;
+    ensure(
+        catalog.package_names() == ["demo", "demo-base"],
+        "unexpected packages",
+    )?;
+
+    let stable = catalog
+        .channel("demo", "stable")
+        .ok_or("channel demo/stable missing")?;
+    ensure(
+        stable.head().is_some_and(|head| head.name == "demo.v1.2.0"),
+        "wrong head of demo/stable",
+    )?;
+    ensure(
+        stable.upgrade_chain().chain.len() == 3,
+        "wrong upgrade chain of demo/stable",
+    )?;
+
+    let report = validate::validate(&catalog, &ValidateOptions::default())?;
+    ensure(
+        !report
+            .findings
+            .iter()
+            .any(|finding| finding.severity == Severity::Error),
+        "validate reports errors",
+    )?;
+
+    ensure(
+        graph::edges(&catalog, &[], None).len() == 4,
+        "wrong number of graph edges",
+    )?;
+
+    let hops = |channel, from, to| match upgrade_path::shortest_path(&catalog, channel, from, to) {
+        UpgradePath::Found(hops) => hops,
+        UpgradePath::NoPath { .. } => Vec::new(),
+    };
+    ensure(
+        matches!(&hops(stable, "demo.v1.0.0", "demo.v1.2.0")[..], [hop] if hop.mechanism == Mechanism::Skips),
+        "demo.v1.2.0 doesn't skip demo.v1.0.0",
+    )?;
+    let fast = catalog
+        .channel("demo", "fast")
+        .ok_or("channel demo/fast missing")?;
+    ensure(
+        matches!(&hops(fast, "demo.v1.0.0", "demo.v1.2.0")[..], [hop] if matches!(hop.mechanism, Mechanism::SkipRange(_))),
+        "skipRange of demo/fast doesn't cover demo.v1.0.0",
+    )?;
+
+    let closure = closure::closure(&catalog, &["demo".to_string()], None)?;
+    ensure(
+        closure.unsatisfied.is_empty() && closure.packages.len() == 2,
+        "closure of demo doesn't pull in demo-base",
+    )?;
+
+    let rendered = write::render(&catalog, &["demo"], write::SortEntries::Source)
+        .map_err(|err| format!("failed to render: {}", err))?;
+    let (reloaded, _) = load(&rendered, "rendered", &limits)?;
+    let (mut original, _) = load(CATALOG_YAML, "catalog.yaml", &limits)?;
+    original.retain_packages(&["demo".to_string()]);
+    ensure(
+        identical::differences(&[
+            ("original".to_string(), original),
+            ("rendered".to_string(), reloaded),
+        ])
+        .is_empty(),
+        "extracted package differs from the original",
+    )?;
+
+    let files = vec![("catalog.yaml".to_string(), summary)];
+    serde_json::to_string(&dump::dump(&catalog, &files))
+        .map_err(|err| format!("failed to dump: {}", err))?;
+    ensure(
+        stats::stats(&files, &catalog, false).total.bundles == 4,
+        "stats miscounts bundles",
+    )
+}
+
+fn json_fbc() -> Result<(), String> {
+    let mut limits = limits(no_substitutions());
+    let (json, summary) = load(CATALOG_JSON, "catalog.json", &limits)?;
+    ensure(summary.failed == 0, "documents failed to parse")?;
+    let (yaml, _) = load(CATALOG_YAML, "catalog.yaml", &limits)?;
+    ensure(
+        identical::differences(&[("yaml".to_string(), yaml), ("json".to_string(), json)])
+            .is_empty(),
+        "JSON catalog differs from the YAML one",
+    )?;
+
+    // The whole stream is over the limit, none of its objects are
+    limits.max_document_size = Limit(Some(1 << 10));
+    let (_, summary) = load(CATALOG_JSON, "catalog.json", &limits)?;
+    ensure(
+        summary.oversized == 0 && summary.bundles == 4,
+        "size limit applied to the JSON stream instead of its objects",
+    )
+}
+
+fn deprecations() -> Result<(), String> {
+    let (catalog, _) = load(
+        DEPRECATIONS,
+        "deprecations.yaml",
+        &limits(no_substitutions()),
+    )?;
+    let deprecations = catalog
+        .deprecations_of("legacy")
+        .ok_or("deprecations of legacy missing")?;
+    ensure(
+        deprecations.package_message().is_some(),
+        "package deprecation missing",
+    )?;
+    ensure(
+        deprecations.bundle_message("legacy.v1.0.0").is_some()
+            && deprecations.bundle_message("legacy.v2.0.0").is_none(),
+        "wrong bundle deprecations",
+    )
+}
+
+fn templates() -> Result<(), String> {
+    let values = [
+        "registry=quay.io/example".to_string(),
+        "env=prod".to_string(),
+    ];
+    let substitutions = Substitutions::new(&values, None, PlaceholderPolicy::Error)?;
+    let (catalog, _) = load(TEMPLATE, "template.yaml", &limits(substitutions))?;
+    ensure(
+        catalog
+            .bundle("templated", "templated.v1.0.0")
+            .is_some_and(|bundle| bundle.image == "quay.io/example/templated-bundle:v1.0.0"),
+        "placeholder in the bundle image not substituted",
+    )?;
+
+    let strict = Substitutions::new(&[], None, PlaceholderPolicy::Error)?;
+    ensure(
+        loader::load_str(TEMPLATE, "template.yaml", &limits(strict)).is_err(),
+        "unsubstituted placeholders accepted with --placeholder-policy error",
+    )
+}
+
+fn broken() -> Result<(), String> {
+    let (catalog, summary) = load(BROKEN, "broken.yaml", &limits(no_substitutions()))?;
+    ensure(summary.failed == 2, "expected 2 invalid documents")?;
+    ensure(
+        summary.unknown == 1,
+        "expected 1 document of unknown schema",
+    )?;
+    ensure(
+        catalog.bundle("broken", "broken.v1.1.0").is_some(),
+        "valid bundle after the broken documents not loaded",
+    )
+}
+
+fn cross_package_replaces() -> Result<(), String> {
+    let (catalog, _) = load(
+        CROSS_REPLACES,
+        "cross-replaces.yaml",
+        &limits(no_substitutions()),
+    )?;
+    let options = ValidateOptions {
+        rules: vec!["dangling-replaces".to_string()],
+        ..ValidateOptions::default()
+    };
+    let report = validate::validate(&catalog, &options)?;
+    let finding = |location: &str| {
+        report
+            .findings
+            .iter()
+            .find(|finding| finding.location == location)
+    };
+    ensure(
+        finding("borrower/stable/borrower.v2.0.0").is_some_and(|finding| {
+            finding.severity == Severity::Error && finding.message.contains("package origin")
+        }),
+        "replaces of a bundle of another package not reported as an error",
+    )?;
+    ensure(
+        finding("borrower/stable/borrower.v3.0.0")
+            .is_some_and(|finding| finding.severity == Severity::Info),
+        "replaces of a pruned bundle not reported as info",
+    )?;
+    ensure(
+        report.findings.len() == 2,
+        "replaces inside the package reported",
+    )
+}
+
+fn identifiers() -> Result<(), String> {
+    let ids = [
+        ObjectId::package("demo"),
+        ObjectId::channel("demo", "stable/v1"),
+        ObjectId::entry("demo", "stable", "demo.v1.0.0"),
+        ObjectId::bundle("demo", "100%.v1.0.0"),
+    ];
+    for id in &ids {
+        let text = id.to_string();
+        ensure(
+            ObjectId::parse(&text).as_ref() == Ok(id),
+            &format!("'{}' doesn't parse back", text),
+        )?;
+    }
+    ensure(
+        ids[1].to_string() == "pkg:demo/channel:stable%2Fv1",
+        "slash in a name not escaped",
+    )?;
+    for malformed in [
+        "",
+        "demo",
+        "pkg:",
+        "pkg:demo/entry:demo.v1.0.0",
+        "pkg:demo/bundle:demo.v1.0.0/entry:x",
+        "pkg:demo/channel:stable/entry:x/y",
+        "pkg:de%2xmo",
+    ] {
+        ensure(
+            ObjectId::parse(malformed).is_err(),
+            &format!("malformed identifier '{}' accepted", malformed),
+        )?;
+    }
+    Ok(())
+}
+
+fn catalog_reader() -> Result<(), String> {
+    let limits = limits(no_substitutions());
+    let items = CatalogReader::new(BROKEN.as_bytes(), "broken.yaml", &limits)
+        .map(|item| match item {
+            Ok((meta, CatalogEntry::OlmPackage(_))) => (meta.index, "package"),
+            Ok((meta, CatalogEntry::OlmBundle(_))) => (meta.index, "bundle"),
+            Ok((meta, _)) => (meta.index, "other"),
+            Err(LoadError::Invalid { meta, .. }) => (meta.index, "invalid"),
+            Err(LoadError::UnknownSchema { meta, .. }) => (meta.index, "unknown"),
+            Err(_) => (u64::MAX, "error"),
+        })
+        .collect::<Vec<_>>();
+    ensure(
+        items
+            == [
+                (0, "package"),
+                (1, "invalid"),
+                (2, "invalid"),
+                (3, "unknown"),
+                (4, "bundle"),
+            ],
+        &format!("unexpected documents {:?}", items),
+    )?;
+
+    let mut reader = CatalogReader::new(BROKEN.as_bytes(), "broken.yaml", &limits);
+    let first = reader.by_ref().take(2).collect::<Vec<_>>();
+    ensure(
+        matches!(first.as_slice(), [Ok(_), Err(LoadError::Invalid { .. })]),
+        "first two documents not a package and an invalid one",
+    )?;
+    match reader.next() {
+        Some(Err(LoadError::Invalid { meta, .. })) => ensure(
+            meta.source == "broken.yaml" && meta.line == 9,
+            &format!("third document at {}:{}", meta.source, meta.line),
+        )?,
+        _ => return Err("reading didn't resume at the third document".to_string()),
+    }
+    Ok(())
+}
+
+fn low_memory() -> Result<(), String> {
+    let mut limits = limits(no_substitutions());
+    limits.low_memory = true;
+    limits.schemas = Some(&["olm.package"]);
+    let (catalog, summary) = load(BROKEN, "broken.yaml", &limits)?;
+    ensure(
+        catalog.package_names() == ["broken"] && catalog.all_bundles().next().is_none(),
+        "documents other than packages loaded",
+    )?;
+    ensure(
+        summary.dropped == 3 && summary.failed == 0,
+        &format!(
+            "expected 3 documents dropped and none failing, got {} and {}",
+            summary.dropped, summary.failed
+        ),
+    )?;
+    ensure(
+        summary.unknown == 1
+            && summary
+                .skipped
+                .iter()
+                .all(|skipped| skipped.content.is_none()),
+        "text of the unknown-schema document kept",
+    )?;
+
+    let (catalog, summary) = load(CATALOG_JSON, "catalog.json", &limits)?;
+    ensure(
+        catalog.package_names() == ["demo", "demo-base"] && summary.dropped > 0,
+        "JSON documents of other schemas not dropped",
+    )?;
+
+    limits.schemas = None;
+    let (_, summary) = load(CATALOG_YAML, "catalog.yaml", &limits)?;
+    ensure(
+        summary.bundle_sizes.is_empty() && summary.bundles > 0,
+        "bundle sizes kept",
+    )
+}
+
+fn untidy_names() -> Result<(), String> {
+    let options = ValidateOptions {
+        rules: vec!["untidy-name".to_string()],
+        ..ValidateOptions::default()
+    };
+    let mut limits = limits(no_substitutions());
+    let (catalog, _) = load(UNTIDY, "untidy.yaml", &limits)?;
+    let report = validate::validate(&catalog, &options)?;
+    ensure(
+        report.findings.len() == 3,
+        &format!("expected 3 untidy names, got {}", report.findings.len()),
+    )?;
+    ensure(
+        write::write_catalog(&catalog, &["etcd"], write::SortEntries::Source, "-", true).is_err(),
+        "catalog with untidy names written without --trim-names",
+    )?;
+
+    limits.trim_names = true;
+    let (catalog, _) = load(UNTIDY, "untidy.yaml", &limits)?;
+    let report = validate::validate(&catalog, &options)?;
+    ensure(
+        report.findings.is_empty(),
+        "untidy names left after trimming",
+    )?;
+    let head = catalog
+        .channel("etcd", "stable")
+        .and_then(|channel| channel.head())
+        .map(|head| head.name.as_str());
+    ensure(
+        head == Some("etcd.v1.1.0") && catalog.bundle("etcd", "etcd.v1.1.0").is_some(),
+        "trimmed replaces and bundle name don't match up",
+    )
+}
+
+/// Run the examples with this very binary in a scratch directory holding
+/// the catalogs they name
+fn examples() -> Result<(), String> {
+    let program = env::current_exe().map_err(|err| format!("no path to this binary: {}", err))?;
+    let scratch = env::temp_dir().join(format!("catalog-browser-examples-{}", process::id()));
+    fs::create_dir_all(&scratch)
+        .and_then(|_| fs::write(scratch.join("catalog.yaml"), CATALOG_YAML))
+        .and_then(|_| fs::write(scratch.join("previous.yaml"), PREVIOUS))
+        .map_err(|err| format!("failed to set up {}: {}", scratch.display(), err))?;
+
+    let result = EXAMPLES.iter().try_for_each(|example| {
+        let output = Command::new(&program)
+            .args(example.args.split_whitespace())
+            .current_dir(&scratch)
+            .output()
+            .map_err(|err| format!("failed to run '{}': {}", example.command_line(), err))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        ensure(
+            output.status.success() && stdout.contains(example.expect),
+            &format!(
+                "'{}' exited with {} without printing '{}'",
+                example.command_line(),
+                output.status,
+                example.expect
+            ),
+        )
+    });
+    let _ = fs::remove_dir_all(&scratch);
+    result
+}
+
+/// Run every scenario against the built-in catalogs, true when all pass
+pub fn run() -> bool {
+    warnings::mute(true);
+    let mut passed = 0;
+    for scenario in SCENARIOS {
+        match (scenario.run)() {
+            Ok(()) => {
+                passed += 1;
+                println!("PASS {}: {}", scenario.name, scenario.description);
+            }
+            Err(err) => println!("FAIL {}: {}: {}", scenario.name, scenario.description, err),
+        }
+    }
+    warnings::mute(false);
+    println!("{} of {} scenarios passed", passed, SCENARIOS.len());
+    passed == SCENARIOS.len()
+}
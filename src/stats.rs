@@ -0,0 +1,269 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::catalog::Catalog;
+use crate::id::ObjectId;
+use crate::loader::{format_size, BundleSize, LoadSummary};
+use crate::output;
+use crate::timestamp::Timestamp;
+
+/// Document counts of one source file or of all of them
+#[derive(Debug, Default, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Counts {
+    pub packages: u64,
+    pub channels: u64,
+    pub bundles: u64,
+    pub deprecations: u64,
+    pub unknown: u64,
+    pub failed: u64,
+    /// Packages the documents belong to, more than one in a file is worth a look
+    pub package_names: BTreeSet<String>,
+}
+
+impl Counts {
+    fn add(&mut self, summary: &LoadSummary) {
+        self.packages += summary.packages;
+        self.channels += summary.channels;
+        self.bundles += summary.bundles;
+        self.deprecations += summary.deprecations;
+        self.unknown += summary.unknown;
+        self.failed += summary.failed;
+        self.package_names
+            .extend(summary.package_names.iter().cloned());
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.packages.to_string(),
+            self.channels.to_string(),
+            self.bundles.to_string(),
+            self.deprecations.to_string(),
+            self.unknown.to_string(),
+            self.failed.to_string(),
+            self.package_names
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Stats<'a> {
+    pub total: Counts,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<BTreeMap<&'a str, Counts>>,
+    /// Oldest and newest bundle of the packages with `createdAt` annotations
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub created: BTreeMap<String, CreatedRange>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedBundle {
+    pub id: String,
+    pub bundle: String,
+    pub created_at: Timestamp,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CreatedRange {
+    pub oldest: CreatedBundle,
+    pub newest: CreatedBundle,
+    /// Bundles of the package without a usable `createdAt`
+    pub unknown: usize,
+}
+
+fn created_ranges(catalog: &Catalog) -> BTreeMap<String, CreatedRange> {
+    let mut ranges: BTreeMap<String, CreatedRange> = BTreeMap::new();
+    let mut unknown: BTreeMap<&str, usize> = BTreeMap::new();
+    for bundle in catalog.all_bundles() {
+        let Some(created_at) = bundle.created_at() else {
+            *unknown.entry(&bundle.package).or_default() += 1;
+            continue;
+        };
+        let created = CreatedBundle {
+            id: ObjectId::bundle(&bundle.package, &bundle.name).to_string(),
+            bundle: bundle.name.clone(),
+            created_at,
+        };
+        match ranges.get_mut(&bundle.package) {
+            Some(range) => {
+                if created.created_at < range.oldest.created_at {
+                    range.oldest = created.clone();
+                }
+                if created.created_at > range.newest.created_at {
+                    range.newest = created;
+                }
+            }
+            None => {
+                ranges.insert(
+                    bundle.package.clone(),
+                    CreatedRange {
+                        oldest: created.clone(),
+                        newest: created,
+                        unknown: 0,
+                    },
+                );
+            }
+        }
+    }
+    for (package, range) in &mut ranges {
+        range.unknown = unknown.get(package.as_str()).copied().unwrap_or_default();
+    }
+    ranges
+}
+
+pub fn stats<'a>(
+    files: &'a [(String, LoadSummary)],
+    catalog: &Catalog,
+    by_file: bool,
+) -> Stats<'a> {
+    let mut total = Counts::default();
+    let mut per_file = BTreeMap::new();
+    for (path, summary) in files {
+        total.add(summary);
+        if by_file {
+            let mut counts = Counts::default();
+            counts.add(summary);
+            per_file.insert(path.as_str(), counts);
+        }
+    }
+    Stats {
+        total,
+        files: by_file.then_some(per_file),
+        created: created_ranges(catalog),
+    }
+}
+
+const HEADERS: [&str; 8] = [
+    "file",
+    "packages",
+    "channels",
+    "bundles",
+    "deprecations",
+    "unknown",
+    "failed",
+    "package names",
+];
+
+pub fn stats_table(stats: &Stats) -> String {
+    let mut rows = Vec::new();
+    for (path, counts) in stats.files.iter().flatten() {
+        let mut row = vec![path.to_string()];
+        row.extend(counts.row());
+        rows.push(row);
+    }
+    let mut total = vec!["total".to_string()];
+    total.extend(stats.total.row());
+    rows.push(total);
+    let mut table = output::table(&HEADERS, &rows);
+
+    if !stats.created.is_empty() {
+        let rows: Vec<Vec<String>> = stats
+            .created
+            .iter()
+            .map(|(package, range)| {
+                vec![
+                    package.clone(),
+                    range.oldest.bundle.clone(),
+                    range.oldest.created_at.to_string(),
+                    range.newest.bundle.clone(),
+                    range.newest.created_at.to_string(),
+                    range.unknown.to_string(),
+                ]
+            })
+            .collect();
+        table.push_str("\n\n");
+        table.push_str(&output::table(
+            &[
+                "package", "oldest", "created", "newest", "created", "unknown",
+            ],
+            &rows,
+        ));
+    }
+    table
+}
+
+/// Bundle among the largest documents of the catalog
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeBundle<'a> {
+    pub id: String,
+    pub file: &'a str,
+    pub version: Option<String>,
+    #[serde(flatten)]
+    pub size: &'a BundleSize,
+}
+
+/// The `count` bundles with the largest source documents, largest first
+pub fn largest<'a>(
+    files: &'a [(String, LoadSummary)],
+    catalog: &Catalog,
+    count: usize,
+) -> Vec<LargeBundle<'a>> {
+    let mut sizes: Vec<(&str, &BundleSize)> = files
+        .iter()
+        .flat_map(|(path, summary)| {
+            summary
+                .bundle_sizes
+                .iter()
+                .map(move |size| (path.as_str(), size))
+        })
+        .collect();
+    sizes.sort_by_key(|(_, size)| std::cmp::Reverse(size.bytes));
+    sizes
+        .into_iter()
+        .take(count)
+        .map(|(file, size)| LargeBundle {
+            id: ObjectId::bundle(&size.package, &size.name).to_string(),
+            file,
+            version: catalog
+                .version_of(&size.package, &size.name)
+                .map(|version| version.to_string()),
+            size,
+        })
+        .collect()
+}
+
+/// Size rounded to a tenth of a KiB or MiB
+pub fn rounded_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format_size(bytes),
+        1024..1048576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
+
+pub fn largest_table(bundles: &[LargeBundle]) -> String {
+    let size = rounded_size;
+    let rows: Vec<Vec<String>> = bundles
+        .iter()
+        .map(|bundle| {
+            vec![
+                bundle.size.package.clone(),
+                bundle.size.name.clone(),
+                bundle.version.clone().unwrap_or_default(),
+                size(bundle.size.bytes),
+                size(bundle.size.properties.bundle_object),
+                size(bundle.size.properties.csv_metadata),
+                size(bundle.size.properties.other),
+            ]
+        })
+        .collect();
+    output::table(
+        &[
+            "package",
+            "bundle",
+            "version",
+            "size",
+            "olm.bundle.object",
+            "olm.csv.metadata",
+            "other properties",
+        ],
+        &rows,
+    )
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+
+/// What to do with `{{...}}` placeholders left after substitution
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Debug, Default)]
+pub enum PlaceholderPolicy {
+    /// Stop loading
+    Error,
+    /// Keep the document and report the placeholder
+    #[default]
+    Warn,
+    /// Keep the document silently
+    Ignore,
+}
+
+/// Values for the placeholders of partially rendered catalogs
+#[derive(Debug, Default)]
+pub struct Substitutions {
+    values: HashMap<String, String>,
+    pub policy: PlaceholderPolicy,
+}
+
+// Nested mappings of a vars file become dotted keys, `Values: {registry: x}`
+// is `Values.registry`
+fn flatten(prefix: &str, value: &serde_yaml::Value, values: &mut HashMap<String, String>) {
+    use serde_yaml::Value;
+    let key = |name: String| {
+        if prefix.is_empty() {
+            name
+        } else {
+            format!("{}.{}", prefix, name)
+        }
+    };
+    match value {
+        Value::Mapping(mapping) => {
+            for (name, value) in mapping {
+                let name = match name {
+                    Value::String(name) => name.clone(),
+                    other => serde_yaml::to_string(other)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                };
+                flatten(&key(name), value, values);
+            }
+        }
+        Value::String(text) => {
+            values.insert(prefix.to_string(), text.clone());
+        }
+        Value::Null => {
+            values.insert(prefix.to_string(), String::new());
+        }
+        other => {
+            let text = serde_yaml::to_string(other).unwrap_or_default();
+            values.insert(prefix.to_string(), text.trim().to_string());
+        }
+    }
+}
+
+impl Substitutions {
+    /// Build from `--substitute key=value` arguments, which win over the
+    /// values of a `--substitute-file`
+    pub fn new(
+        pairs: &[String],
+        file: Option<&str>,
+        policy: PlaceholderPolicy,
+    ) -> Result<Substitutions, String> {
+        let mut values = HashMap::new();
+        if let Some(file) = file {
+            let content = fs::read_to_string(file)
+                .map_err(|err| format!("Failed to read {}: {}", file, err))?;
+            let vars: serde_yaml::Value = serde_yaml::from_str(&content)
+                .map_err(|err| format!("Invalid substitution file {}: {}", file, err))?;
+            flatten("", &vars, &mut values);
+        }
+        for pair in pairs {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("invalid --substitute '{}', expected key=value", pair))?;
+            values.insert(key.trim().to_string(), value.to_string());
+        }
+        Ok(Substitutions { values, policy })
+    }
+
+    /// Value of a placeholder, `{{ .Values.registry }}` matches the keys
+    /// `Values.registry` and `registry`
+    fn lookup(&self, placeholder: &str) -> Option<&str> {
+        let name = placeholder.trim().trim_start_matches('.');
+        self.values
+            .get(name)
+            .or_else(|| self.values.get(name.strip_prefix("Values.")?))
+            .map(String::as_str)
+    }
+
+    /// Replace every placeholder with a known value, leaving the rest untouched
+    /// No values to substitute, the text stays as it is
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        if self.values.is_empty() {
+            return text.to_string();
+        }
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(length) = rest[start..].find("}}") else {
+                break;
+            };
+            let placeholder = &rest[start + 2..start + length];
+            result.push_str(&rest[..start]);
+            match self.lookup(placeholder) {
+                Some(value) => result.push_str(value),
+                None => result.push_str(&rest[start..start + length + 2]),
+            }
+            rest = &rest[start + length + 2..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// First `{{...}}` placeholder of a text
+pub fn first_placeholder(text: &str) -> Option<&str> {
+    let start = text.find("{{")?;
+    let length = text[start..].find("}}")?;
+    Some(&text[start..start + length + 2])
+}
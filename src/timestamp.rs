@@ -0,0 +1,128 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Point in time from a `createdAt` annotation, kept as UTC seconds so
+/// values with different offsets compare correctly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(i64);
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn number(text: &str, digits: usize) -> Option<i64> {
+    (text.len() == digits && text.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| text.parse().ok())
+        .flatten()
+}
+
+impl Timestamp {
+    /// Accepts RFC 3339 and the variants seen in CSVs: a space instead of
+    /// `T`, no seconds, fractional seconds, no zone (taken as UTC), a
+    /// `+0100` offset, `UTC` as the zone, or a bare date
+    pub fn parse(text: &str) -> Option<Timestamp> {
+        let text = text.trim();
+        let (date, time) = match text.find(['T', 't', ' ']) {
+            Some(index) => (&text[..index], text[index + 1..].trim()),
+            None => (text, ""),
+        };
+        let mut parts = date.splitn(3, '-');
+        let year = number(parts.next()?, 4)?;
+        let month = number(parts.next()?, 2)?;
+        let day = number(parts.next()?, 2)?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        let (clock, offset) = if time.is_empty() {
+            ("00:00:00", 0)
+        } else if let Some(clock) = time
+            .strip_suffix(['Z', 'z'])
+            .or_else(|| time.strip_suffix("UTC").map(str::trim_end))
+        {
+            (clock, 0)
+        } else {
+            match time.rfind(['+', '-']) {
+                Some(index) => {
+                    let zone = time[index + 1..].replace(':', "");
+                    let hours = number(zone.get(..2)?, 2)?;
+                    let minutes = number(zone.get(2..)?, 2)?;
+                    let sign = if time[index..].starts_with('-') {
+                        -1
+                    } else {
+                        1
+                    };
+                    (&time[..index], sign * (hours * 3600 + minutes * 60))
+                }
+                None => (time, 0),
+            }
+        };
+        let clock = clock.split('.').next()?;
+        let mut fields = clock.split(':');
+        let hour = number(fields.next()?, 2)?;
+        let minute = number(fields.next()?, 2)?;
+        let second = fields.next().map_or(Some(0), |second| number(second, 2))?;
+        if fields.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+
+        let days = days_from_civil(year, month, day);
+        Some(Timestamp(
+            days * 86400 + hour * 3600 + minute * 60 + second - offset,
+        ))
+    }
+}
+
+/// Normalized to RFC 3339 in UTC
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.0.div_euclid(86400));
+        let seconds = self.0.rem_euclid(86400);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        )
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl schemars::JsonSchema for Timestamp {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Timestamp".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({ "type": "string", "format": "date-time" })
+    }
+}
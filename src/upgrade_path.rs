@@ -0,0 +1,104 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::catalog::{Catalog, Channel, ChannelEntry};
+use crate::version::{Version, VersionRange};
+
+/// Which part of an entry lets OLM take a hop
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mechanism {
+    Replaces,
+    Skips,
+    SkipRange(String),
+}
+
+impl fmt::Display for Mechanism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mechanism::Replaces => write!(f, "replaces"),
+            Mechanism::Skips => write!(f, "skips"),
+            Mechanism::SkipRange(range) => write!(f, "skipRange {}", range),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hop {
+    pub from: String,
+    pub to: String,
+    pub mechanism: Mechanism,
+}
+
+/// Shortest upgrade path between two bundles, or how close the search got
+#[derive(Debug)]
+pub enum UpgradePath {
+    Found(Vec<Hop>),
+    NoPath {
+        /// Reachable bundle with the highest version not above the target
+        nearest: Option<String>,
+    },
+}
+
+// Same edges as ChannelEntry::upgrades_from, telling which one applies
+fn mechanism(entry: &ChannelEntry, from: &str, version: Option<&Version>) -> Option<Mechanism> {
+    if entry.replaces == from {
+        return Some(Mechanism::Replaces);
+    }
+    if entry.skips.iter().any(|skip| skip == from) {
+        return Some(Mechanism::Skips);
+    }
+    let range = entry.skip_range.as_ref()?;
+    let matches = VersionRange::parse(range)
+        .ok()
+        .zip(version)
+        .is_some_and(|(parsed, version)| parsed.matches(version));
+    matches.then(|| Mechanism::SkipRange(range.clone()))
+}
+
+/// Breadth first search over replaces, skips and skipRange edges of the channel
+pub fn shortest_path(catalog: &Catalog, channel: &Channel, from: &str, to: &str) -> UpgradePath {
+    let version_of = |name: &str| catalog.version_of(&channel.package, name);
+    // How each reached bundle was reached
+    let mut reached: HashMap<String, Option<Hop>> = HashMap::from([(from.to_string(), None)]);
+    let mut queue = VecDeque::from([from.to_string()]);
+
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            let mut hops = Vec::new();
+            let mut name = current;
+            while let Some(Some(hop)) = reached.get(&name) {
+                name = hop.from.clone();
+                hops.push(hop.clone());
+            }
+            hops.reverse();
+            return UpgradePath::Found(hops);
+        }
+
+        let version = version_of(&current);
+        for entry in &channel.entries {
+            if reached.contains_key(&entry.name) {
+                continue;
+            }
+            if let Some(mechanism) = mechanism(entry, &current, version.as_ref()) {
+                reached.insert(
+                    entry.name.clone(),
+                    Some(Hop {
+                        from: current.clone(),
+                        to: entry.name.clone(),
+                        mechanism,
+                    }),
+                );
+                queue.push_back(entry.name.clone());
+            }
+        }
+    }
+
+    let target = version_of(to);
+    let nearest = reached
+        .keys()
+        .filter_map(|name| Some((version_of(name)?, name)))
+        .filter(|(version, _)| target.as_ref().is_none_or(|target| version <= target))
+        .max()
+        .map(|(_, name)| name.clone());
+    UpgradePath::NoPath { nearest }
+}
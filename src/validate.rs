@@ -0,0 +1,562 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::catalog::Catalog;
+use crate::image::{ImageReference, ReferenceProblem};
+use crate::names;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+impl Severity {
+    /// Label for a count of findings, e.g. `3 warnings`
+    pub fn plural(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warnings",
+            Severity::Error => "errors",
+        }
+    }
+}
+
+/// Single problem reported by a rule
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Finding {
+    pub severity: Severity,
+    pub rule: &'static str,
+    /// Catalog object the finding is about, e.g. `etcd/etcd.v0.9.2`
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}: {}",
+            self.severity, self.rule, self.location, self.message
+        )
+    }
+}
+
+pub struct Rule {
+    pub id: &'static str,
+    pub description: &'static str,
+    /// Opt-in rules only run when enabled explicitly
+    pub default_enabled: bool,
+    check: fn(&Catalog, &mut Vec<Finding>),
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "bundle-name-version",
+        description: "Bundle name version suffix matches its olm.package version",
+        default_enabled: false,
+        check: check_bundle_name_version,
+    },
+    Rule {
+        id: "non-standard-keys",
+        description: "Non-standard top-level keys on olm.channel documents",
+        default_enabled: true,
+        check: check_non_standard_keys,
+    },
+    Rule {
+        id: "replaces-in-skips",
+        description: "Channel entry skips the bundle it already replaces",
+        default_enabled: true,
+        check: check_replaces_in_skips,
+    },
+    Rule {
+        id: "skips-replaces-ancestor",
+        description: "Channel entry skips a bundle its replaces chain already passes through",
+        default_enabled: true,
+        check: check_skips_replaces_ancestor,
+    },
+    Rule {
+        id: "dangling-replaces",
+        description: "Channel entry replaces a bundle missing from its package",
+        default_enabled: true,
+        check: check_dangling_replaces,
+    },
+    Rule {
+        id: "malformed-property",
+        description: "Bundle property whose value doesn't have the shape its type calls for",
+        default_enabled: true,
+        check: check_malformed_property,
+    },
+    Rule {
+        id: "untidy-name",
+        description:
+            "Name, package, replaces or skips value with whitespace around it or control characters",
+        default_enabled: true,
+        check: check_untidy_name,
+    },
+    Rule {
+        id: "shared-bundle-image",
+        description: "Bundle image reference used by more than one bundle",
+        default_enabled: true,
+        check: check_shared_bundle_image,
+    },
+    Rule {
+        id: "image-reference",
+        description: "Bundle and related image references that aren't well-formed",
+        default_enabled: true,
+        check: check_image_reference,
+    },
+];
+
+/// Compare the `.vX.Y.Z` suffix of bundle names with the olm.package property
+fn check_bundle_name_version(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    for bundle in catalog.all_bundles() {
+        let location = format!("{}/{}", bundle.package, bundle.name);
+        match (bundle.name_version(), bundle.version()) {
+            (Some(name_version), Some(version)) if name_version != version => {
+                findings.push(Finding {
+                    severity: Severity::Warning,
+                    rule: "bundle-name-version",
+                    location,
+                    message: format!(
+                        "bundle name says v{} but olm.package property says {}",
+                        name_version, version
+                    ),
+                });
+            }
+            (None, _) => findings.push(Finding {
+                severity: Severity::Info,
+                rule: "bundle-name-version",
+                location,
+                message: "bundle name doesn't follow the <name>.vX.Y.Z convention".to_string(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Count the vendor extension keys preserved on channels
+fn check_non_standard_keys(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for channel in catalog.all_channels() {
+        for key in channel.extra.keys() {
+            let key = match key.as_str() {
+                Some(key) => key.to_string(),
+                None => format!("{:?}", key),
+            };
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    for (key, count) in counts {
+        findings.push(Finding {
+            severity: Severity::Info,
+            rule: "non-standard-keys",
+            location: "olm.channel".to_string(),
+            message: format!("non-standard key '{}' appears on {} channels", key, count),
+        });
+    }
+}
+
+/// The same bundle in both `replaces` and `skips` of an entry
+fn check_replaces_in_skips(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    for channel in catalog.all_channels() {
+        for entry in &channel.entries {
+            if entry.replaces.is_empty() || !entry.skips.contains(&entry.replaces) {
+                continue;
+            }
+            findings.push(Finding {
+                severity: Severity::Warning,
+                rule: "replaces-in-skips",
+                location: format!("{}/{}/{}", channel.package, channel.name, entry.name),
+                message: format!("'{}' is both replaced and skipped", entry.replaces),
+            });
+        }
+    }
+}
+
+/// Skips of bundles further down the replaces chain, which the upgrade
+/// graph reaches anyway. The direct replaces is left to replaces-in-skips.
+fn check_skips_replaces_ancestor(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    for channel in catalog.all_channels() {
+        for entry in &channel.entries {
+            let chain = channel.replaces_chain(entry);
+            // The chain ends with the entry itself and its direct replaces
+            let ancestors = &chain[..chain.len().saturating_sub(2)];
+            for skip in &entry.skips {
+                if !ancestors.iter().any(|ancestor| &ancestor.name == skip) {
+                    continue;
+                }
+                findings.push(Finding {
+                    severity: Severity::Info,
+                    rule: "skips-replaces-ancestor",
+                    location: format!("{}/{}/{}", channel.package, channel.name, entry.name),
+                    message: format!(
+                        "skipped '{}' is already an ancestor through replaces '{}'",
+                        skip, entry.replaces
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Replaces targets that aren't a bundle or channel entry of the package.
+/// Pruned catalogs leave those behind on purpose, but a target that is a
+/// bundle of another package is a mix-up fixed differently than a typo.
+fn check_dangling_replaces(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    let mut owners: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for bundle in catalog.all_bundles() {
+        owners
+            .entry(&bundle.name)
+            .or_default()
+            .push(&bundle.package);
+    }
+
+    for channel in catalog.all_channels() {
+        for entry in &channel.entries {
+            if entry.replaces.is_empty()
+                || channel.entry(&entry.replaces).is_some()
+                || catalog.bundle(&channel.package, &entry.replaces).is_some()
+            {
+                continue;
+            }
+            let location = format!("{}/{}/{}", channel.package, channel.name, entry.name);
+            let elsewhere: Vec<&str> = owners
+                .get(entry.replaces.as_str())
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|package| *package != channel.package)
+                .collect();
+            let finding = if elsewhere.is_empty() {
+                Finding {
+                    severity: Severity::Info,
+                    rule: "dangling-replaces",
+                    location,
+                    message: format!(
+                        "replaces target '{}' not found in package {}",
+                        entry.replaces, channel.package
+                    ),
+                }
+            } else {
+                Finding {
+                    severity: Severity::Error,
+                    rule: "dangling-replaces",
+                    location,
+                    message: format!(
+                        "replaces target '{}' exists in package {}, not in package {}",
+                        entry.replaces,
+                        elsewhere.join(", "),
+                        channel.package
+                    ),
+                }
+            };
+            findings.push(finding);
+        }
+    }
+}
+
+/// Properties the loader kept as written because their value doesn't match
+/// their type, typed lookups like the bundle version ignore them
+fn check_malformed_property(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    for bundle in catalog.all_bundles() {
+        for (index, property) in bundle.properties.iter().enumerate() {
+            let Some(reason) = &property.malformed else {
+                continue;
+            };
+            findings.push(Finding {
+                severity: Severity::Error,
+                rule: "malformed-property",
+                location: format!("{}/{}", bundle.package, bundle.name),
+                message: format!(
+                    "properties[{}] ({}): {}",
+                    index,
+                    property.display_type(),
+                    reason
+                ),
+            });
+        }
+    }
+}
+
+/// Names that never match anything as written, `--trim-names` tidies them
+/// while loading
+fn check_untidy_name(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    for name in names::untidy(catalog, &catalog.package_names()) {
+        findings.push(Finding {
+            severity: Severity::Error,
+            rule: "untidy-name",
+            location: name.location.clone(),
+            message: name.to_string(),
+        });
+    }
+}
+
+/// Different bundles pointing at the same image. A shared tag means one of
+/// them ships content it wasn't built with, a shared digest is only suspicious.
+fn check_shared_bundle_image(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    let mut by_image: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for bundle in catalog.all_bundles() {
+        by_image
+            .entry(&bundle.image)
+            .or_default()
+            .push(format!("{}/{}", bundle.package, bundle.name));
+    }
+
+    for (image, mut bundles) in by_image {
+        bundles.sort();
+        bundles.dedup();
+        if bundles.len() < 2 {
+            continue;
+        }
+        let (severity, kind) = if ImageReference::parse(image).is_digest_pinned() {
+            (Severity::Info, "digest-pinned image")
+        } else {
+            (Severity::Error, "tag-based image")
+        };
+        findings.push(Finding {
+            severity,
+            rule: "shared-bundle-image",
+            location: image.to_string(),
+            message: format!("{} shared by bundles {}", kind, bundles.join(", ")),
+        });
+    }
+}
+
+/// Strict syntax check of every bundle and related image. Broken references
+/// are errors, ones relying on docker.io are warnings since clusters often
+/// can't reach it, and uppercase repositories only need lowercasing.
+fn check_image_reference(catalog: &Catalog, findings: &mut Vec<Finding>) {
+    for bundle in catalog.all_bundles() {
+        let location = format!("{}/{}", bundle.package, bundle.name);
+        let images = std::iter::once(("bundle image", bundle.image.as_str())).chain(
+            bundle
+                .related_images
+                .iter()
+                .map(|related| ("related image", related.image.as_str())),
+        );
+        for (kind, image) in images {
+            for problem in ImageReference::problems(image) {
+                let (severity, message) = match problem {
+                    ReferenceProblem::Invalid(reason) => (
+                        Severity::Error,
+                        format!("{} '{}' is invalid: {}", kind, image, reason),
+                    ),
+                    ReferenceProblem::ImplicitDockerHub => (
+                        Severity::Warning,
+                        format!(
+                            "{} '{}' has no registry and resolves to docker.io",
+                            kind, image
+                        ),
+                    ),
+                    ReferenceProblem::Uppercase => (
+                        Severity::Info,
+                        format!("{} '{}' has an uppercase repository name", kind, image),
+                    ),
+                };
+                findings.push(Finding {
+                    severity,
+                    rule: "image-reference",
+                    location: location.clone(),
+                    message,
+                });
+            }
+        }
+    }
+}
+
+/// Which rules run and how
+#[derive(Debug, Default)]
+pub struct ValidateOptions {
+    /// Opt-in rules to run as well
+    pub enable: Vec<String>,
+    pub disable: Vec<String>,
+    /// Run only these rules, default and opt-in alike
+    pub rules: Vec<String>,
+    /// Stop at the first error finding
+    pub fail_fast: bool,
+}
+
+pub struct Report {
+    pub findings: Vec<Finding>,
+    /// --fail-fast stopped the evaluation, rules may have been left out
+    pub cut_short: bool,
+}
+
+/// Run the default rules plus the enabled opt-in ones, or only the selected
+/// ones, minus the disabled ones. Findings are sorted by severity and location.
+pub fn validate(catalog: &Catalog, options: &ValidateOptions) -> Result<Report, String> {
+    if let Some(unknown) = options
+        .enable
+        .iter()
+        .chain(&options.disable)
+        .chain(&options.rules)
+        .find(|id| !RULES.iter().any(|rule| rule.id == id.as_str()))
+    {
+        return Err(format!("unknown validation rule '{}'", unknown));
+    }
+
+    let listed = |ids: &[String], rule: &Rule| ids.iter().any(|id| id == rule.id);
+    let mut findings = Vec::new();
+    for rule in RULES {
+        let selected = if options.rules.is_empty() {
+            rule.default_enabled || listed(&options.enable, rule)
+        } else {
+            listed(&options.rules, rule)
+        };
+        if !selected || listed(&options.disable, rule) {
+            continue;
+        }
+
+        let start = findings.len();
+        (rule.check)(catalog, &mut findings);
+        if options.fail_fast {
+            // Rules go through hash maps, sort so every run stops at the same error
+            findings[start..].sort_by(|a, b| {
+                a.location
+                    .cmp(&b.location)
+                    .then_with(|| a.message.cmp(&b.message))
+            });
+            if let Some(index) = findings[start..]
+                .iter()
+                .position(|finding| finding.severity == Severity::Error)
+            {
+                let first = findings.remove(start + index);
+                return Ok(Report {
+                    findings: vec![first],
+                    cut_short: true,
+                });
+            }
+        }
+    }
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.location.cmp(&b.location))
+    });
+
+    Ok(Report {
+        findings,
+        cut_short: false,
+    })
+}
+
+/// How many findings of each severity text output shows by default
+pub const DEFAULT_MAX_FINDINGS: usize = 200;
+
+/// Summarized view of the findings
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    /// One line per rule with counts and sample locations
+    Rule,
+}
+
+/// First `max` findings of each severity, in order, with the number left
+/// out per severity. `max` of 0 keeps everything.
+pub fn truncate(findings: &[Finding], max: usize) -> (Vec<&Finding>, Vec<(Severity, usize)>) {
+    if max == 0 {
+        return (findings.iter().collect(), Vec::new());
+    }
+    let mut seen: BTreeMap<Severity, usize> = BTreeMap::new();
+    let mut kept = Vec::new();
+    for finding in findings {
+        let count = seen.entry(finding.severity).or_default();
+        *count += 1;
+        if *count <= max {
+            kept.push(finding);
+        }
+    }
+    let omitted = seen
+        .into_iter()
+        .rev()
+        .filter(|(_, count)| *count > max)
+        .map(|(severity, count)| (severity, count - max))
+        .collect();
+    (kept, omitted)
+}
+
+/// Locations given as samples of each rule group
+const GROUP_SAMPLES: usize = 3;
+
+/// Findings of one rule
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RuleGroup {
+    pub rule: &'static str,
+    pub errors: usize,
+    pub warnings: usize,
+    pub info: usize,
+    /// First locations the rule reported
+    pub samples: Vec<String>,
+}
+
+impl RuleGroup {
+    fn total(&self) -> usize {
+        self.errors + self.warnings + self.info
+    }
+}
+
+/// Findings grouped by rule, the rules with most errors first, then most findings
+pub fn group_by_rule(findings: &[Finding]) -> Vec<RuleGroup> {
+    let mut groups: BTreeMap<&'static str, RuleGroup> = BTreeMap::new();
+    for finding in findings {
+        let group = groups.entry(finding.rule).or_insert_with(|| RuleGroup {
+            rule: finding.rule,
+            errors: 0,
+            warnings: 0,
+            info: 0,
+            samples: Vec::new(),
+        });
+        match finding.severity {
+            Severity::Error => group.errors += 1,
+            Severity::Warning => group.warnings += 1,
+            Severity::Info => group.info += 1,
+        }
+        if group.samples.len() < GROUP_SAMPLES && !group.samples.contains(&finding.location) {
+            group.samples.push(finding.location.clone());
+        }
+    }
+    let mut groups: Vec<RuleGroup> = groups.into_values().collect();
+    groups.sort_by_key(|group| {
+        (
+            std::cmp::Reverse(group.errors),
+            std::cmp::Reverse(group.total()),
+        )
+    });
+    groups
+}
+
+impl fmt::Display for RuleGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: Vec<String> = [
+            (Severity::Error, self.errors),
+            (Severity::Warning, self.warnings),
+            (Severity::Info, self.info),
+        ]
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(severity, count)| format!("{} {}", count, severity.plural()))
+        .collect();
+        write!(f, "{}: {}", self.rule, counts.join(", "))?;
+        let more = self.total().saturating_sub(self.samples.len());
+        write!(f, " (e.g. {}", self.samples.join(", "))?;
+        if more > 0 {
+            write!(f, ", ...")?;
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,272 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Semantic version as used by the olm.package property
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<String>,
+    pub build: String,
+}
+
+impl Version {
+    /// Parse a strict `MAJOR.MINOR.PATCH[-pre][+build]` version
+    pub fn parse(input: &str) -> Result<Version, String> {
+        let input = input.trim();
+        let (rest, build) = match input.split_once('+') {
+            Some((rest, build)) => (rest, build.to_string()),
+            None => (input, String::new()),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, pre.split('.').map(String::from).collect()),
+            None => (rest, Vec::new()),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.len() != 3 {
+            return Err(format!("invalid version '{}'", input));
+        }
+        let number = |part: &str| {
+            // Numeric parts must not have leading zeros
+            if part.len() > 1 && part.starts_with('0') {
+                return Err(format!("invalid version '{}'", input));
+            }
+            part.parse::<u64>()
+                .map_err(|_| format!("invalid version '{}'", input))
+        };
+
+        Ok(Version {
+            major: number(parts[0])?,
+            minor: number(parts[1])?,
+            patch: number(parts[2])?,
+            pre,
+            build,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build)?;
+        }
+        Ok(())
+    }
+}
+
+// Compare a single pre-release identifier: numeric ones are lower than
+// alphanumeric ones and compare numerically between themselves
+fn compare_identifier(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    for (a, b) in self.pre.iter().zip(&other.pre) {
+                        let ordering = compare_identifier(a, b);
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+                    self.pre.len().cmp(&other.pre.len())
+                }
+            })
+            // Build metadata has no precedence, it only keeps Ord consistent with
+            // Eq. Numeric identifiers equal as numbers, like `01` and `1`, still
+            // differ as strings.
+            .then_with(|| {
+                let mut a = self.build.split('.');
+                let mut b = other.build.split('.');
+                loop {
+                    match (a.next(), b.next()) {
+                        (Some(a), Some(b)) => match compare_identifier(a, b).then_with(|| a.cmp(b))
+                        {
+                            Ordering::Equal => continue,
+                            ordering => return ordering,
+                        },
+                        (a, b) => return a.is_some().cmp(&b.is_some()),
+                    }
+                }
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug, Clone)]
+struct Comparator {
+    op: Operator,
+    version: Version,
+}
+
+impl Comparator {
+    fn matches(&self, version: &Version) -> bool {
+        match self.op {
+            Operator::Equal => version == &self.version,
+            Operator::NotEqual => version != &self.version,
+            Operator::Greater => version > &self.version,
+            Operator::GreaterOrEqual => version >= &self.version,
+            Operator::Less => version < &self.version,
+            Operator::LessOrEqual => version <= &self.version,
+        }
+    }
+}
+
+/// Version range in the syntax OLM uses for skipRange and versionRange,
+/// e.g. `>=4.1.0 <4.2.0 || >=4.3.0`
+#[derive(Debug, Clone)]
+pub struct VersionRange {
+    raw: String,
+    // Alternatives separated by `||`, each one a set of comparators that must all match
+    alternatives: Vec<Vec<Comparator>>,
+}
+
+impl VersionRange {
+    pub fn parse(input: &str) -> Result<VersionRange, String> {
+        let mut alternatives = Vec::new();
+        for alternative in input.split("||") {
+            let mut comparators = Vec::new();
+            let mut pending_op: Option<&str> = None;
+            for token in alternative.split_whitespace() {
+                // Allow whitespace between the operator and the version (">= 1.0.0")
+                let token = match pending_op.take() {
+                    Some(op) => format!("{}{}", op, token),
+                    None => token.to_string(),
+                };
+                if token.chars().all(|c| "<>=!".contains(c)) {
+                    pending_op = Some(match token.as_str() {
+                        ">=" => ">=",
+                        "<=" => "<=",
+                        ">" => ">",
+                        "<" => "<",
+                        "=" | "==" => "=",
+                        "!=" => "!=",
+                        _ => return Err(format!("invalid version range '{}'", input)),
+                    });
+                    continue;
+                }
+                comparators.push(
+                    parse_comparator(&token)
+                        .map_err(|err| format!("invalid version range '{}': {}", input, err))?,
+                );
+            }
+            if pending_op.is_some() || comparators.is_empty() {
+                return Err(format!("invalid version range '{}'", input));
+            }
+            alternatives.push(comparators);
+        }
+
+        Ok(VersionRange {
+            raw: input.trim().to_string(),
+            alternatives,
+        })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.alternatives
+            .iter()
+            .any(|comparators| comparators.iter().all(|c| c.matches(version)))
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+fn parse_comparator(token: &str) -> Result<Comparator, String> {
+    let (op, version) = if let Some(rest) = token.strip_prefix(">=") {
+        (Operator::GreaterOrEqual, rest)
+    } else if let Some(rest) = token.strip_prefix("<=") {
+        (Operator::LessOrEqual, rest)
+    } else if let Some(rest) = token.strip_prefix("!=") {
+        (Operator::NotEqual, rest)
+    } else if let Some(rest) = token.strip_prefix("==") {
+        (Operator::Equal, rest)
+    } else if let Some(rest) = token.strip_prefix('>') {
+        (Operator::Greater, rest)
+    } else if let Some(rest) = token.strip_prefix('<') {
+        (Operator::Less, rest)
+    } else if let Some(rest) = token.strip_prefix('=') {
+        (Operator::Equal, rest)
+    } else {
+        (Operator::Equal, token)
+    };
+
+    Ok(Comparator {
+        op,
+        version: Version::parse(version)?,
+    })
+}
+
+/// Parse the major and minor part of a version like `4.12` or `4.12.3`
+pub fn parse_major_minor(input: &str) -> Option<(u64, u64)> {
+    let mut parts = input.trim().trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(input: &str) -> Version {
+        Version::parse(input).expect("valid version")
+    }
+
+    #[test]
+    fn build_metadata_ordering_agrees_with_eq() {
+        let pairs = [
+            ("1.0.0+01", "1.0.0+1"),
+            ("1.0.0+2", "1.0.0+10"),
+            ("1.0.0+build.1", "1.0.0+build.01"),
+            ("1.0.0", "1.0.0+1"),
+        ];
+        for (a, b) in pairs {
+            let (a, b) = (version(a), version(b));
+            assert_eq!(a == b, a.cmp(&b) == Ordering::Equal, "{} and {}", a, b);
+            assert_eq!(a.cmp(&b), b.cmp(&a).reverse(), "{} and {}", a, b);
+        }
+        assert!(version("1.0.0+2") < version("1.0.0+10"));
+    }
+
+    #[test]
+    fn build_metadata_has_no_precedence_over_pre_release() {
+        assert!(version("1.0.0-rc.1+99") < version("1.0.0+1"));
+        assert!(version("1.0.0-2+1") < version("1.0.0-10+0"));
+    }
+}
@@ -0,0 +1,144 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::{output, pager};
+
+/// How non-fatal issues are reported on stderr
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Debug, Default)]
+pub enum WarningsFormat {
+    /// One line of text per issue as it happens
+    #[default]
+    Text,
+    /// A single JSON document with every issue when the command ends
+    Json,
+}
+
+/// Non-fatal issue met while loading or running a command
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Warning {
+    /// Stable identifier, e.g. `document-invalid`
+    pub code: &'static str,
+    /// Text printed on stderr with the default text format
+    pub message: String,
+    /// Catalog file, or `file:name/key` for documents of a ConfigMap or an
+    /// annotation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Line the document starts on, 1-based
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+impl Warning {
+    pub fn new(code: &'static str, message: String) -> Warning {
+        Warning {
+            code,
+            message,
+            file: None,
+            line: None,
+        }
+    }
+
+    pub fn in_file(mut self, file: &str) -> Warning {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    pub fn at(self, file: &str, line: usize) -> Warning {
+        let mut warning = self.in_file(file);
+        warning.line = Some(line);
+        warning
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Some(file), Some(line)) = (&self.file, self.line) else {
+            return write!(f, "{}", self.message);
+        };
+        let lead = match self.code {
+            "document-invalid" => "Failed to deserialize the document at",
+            "document-limit" => "Stopping at",
+            "placeholder-unsubstituted" => "Unsubstituted placeholder in the document at",
+            "property-malformed" => "Malformed property in the document at",
+            "name-untidy" => "Untidy name in the document at",
+            _ => "Skipping the document at",
+        };
+        write!(f, "{} {}:{}: {}", lead, file, line, self.message)
+    }
+}
+
+static FORMAT: OnceLock<WarningsFormat> = OnceLock::new();
+static COLLECTED: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+static MUTED: AtomicBool = AtomicBool::new(false);
+
+/// Drop warnings instead of reporting them, for runs over built-in catalogs
+pub fn mute(muted: bool) {
+    MUTED.store(muted, Ordering::Relaxed);
+}
+
+pub fn init(format: WarningsFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// Whether warnings end up in the JSON document, which carries even the
+/// ones only printed with --verbose otherwise
+pub fn structured() -> bool {
+    FORMAT.get() == Some(&WarningsFormat::Json)
+}
+
+pub fn warn(warning: Warning) {
+    if MUTED.load(Ordering::Relaxed) {
+        return;
+    }
+    if structured() {
+        COLLECTED
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(warning);
+    } else {
+        eprintln!("{}", warning);
+    }
+}
+
+/// Print the collected warnings, a no-op for text warnings
+pub fn flush() {
+    if !structured() {
+        return;
+    }
+    let items = std::mem::take(&mut *COLLECTED.lock().unwrap_or_else(|err| err.into_inner()));
+    match output::json_document("WarningList", output::Items { items }) {
+        Ok(document) => eprintln!("{}", document),
+        Err(err) => eprintln!("Failed to serialize warnings: {}", err),
+    }
+}
+
+/// Exit with the given code, flushing the collected warnings first
+pub fn exit(code: i32) -> ! {
+    flush();
+    pager::finish();
+    std::process::exit(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_lists_every_serialized_field() {
+        let schema = serde_json::to_value(schemars::schema_for!(Warning)).unwrap();
+        let warning = Warning::new("document-invalid", "bad".to_string()).at("catalog.yaml", 3);
+        let serialized = serde_json::to_value(&warning).unwrap();
+
+        let mut properties: Vec<&String> =
+            schema["properties"].as_object().unwrap().keys().collect();
+        let mut fields: Vec<&String> = serialized.as_object().unwrap().keys().collect();
+        properties.sort();
+        fields.sort();
+        assert_eq!(properties, fields);
+        assert_eq!(schema["required"], serde_json::json!(["code", "message"]));
+    }
+}
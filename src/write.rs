@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+use crate::catalog::{Catalog, CatalogEntry, Channel};
+use crate::diff;
+use crate::loader::{self, LoadLimits};
+use crate::names;
+use crate::version::Version;
+
+/// Order of channel entries in written catalogs
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+pub enum SortEntries {
+    /// Keep entries exactly as authored
+    Source,
+    /// Sort entries by bundle version, unversioned ones last in source order
+    Semver,
+}
+
+fn sorted_channel(channel: &Channel, catalog: &Catalog) -> Channel {
+    let mut channel = channel.clone();
+    let version_of = |name: &str| -> Option<Version> { catalog.version_of(&channel.package, name) };
+    let mut keyed: Vec<_> = channel
+        .entries
+        .drain(..)
+        .map(|entry| (version_of(&entry.name), entry))
+        .collect();
+    // Stable sort so entries without a version keep their relative order
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    channel.entries = keyed.into_iter().map(|(_, entry)| entry).collect();
+    channel
+}
+
+/// Render the documents of the given packages as a multi-document YAML stream,
+/// package first, then its channels, bundles and deprecations in source order
+pub fn render(
+    catalog: &Catalog,
+    packages: &[&str],
+    sort: SortEntries,
+) -> Result<String, serde_yaml::Error> {
+    let mut documents = Vec::new();
+    for &package in packages {
+        if let Some(entry) = catalog.packages.get(package) {
+            documents.push(serde_yaml::to_string(entry)?);
+        }
+        for channel in catalog.channels_of(package) {
+            let document = match sort {
+                SortEntries::Source => {
+                    serde_yaml::to_string(&CatalogEntry::OpmChannel(channel.clone()))?
+                }
+                SortEntries::Semver => serde_yaml::to_string(&CatalogEntry::OpmChannel(
+                    sorted_channel(channel, catalog),
+                ))?,
+            };
+            documents.push(document);
+        }
+        for entry in catalog.bundles.get(package).into_iter().flatten() {
+            documents.push(serde_yaml::to_string(entry)?);
+        }
+        if let Some(entry) = catalog.deprecations.get(package) {
+            documents.push(serde_yaml::to_string(entry)?);
+        }
+    }
+
+    Ok(documents
+        .iter()
+        .map(|document| format!("---\n{}", document))
+        .collect())
+}
+
+pub fn write_catalog(
+    catalog: &Catalog,
+    packages: &[&str],
+    sort: SortEntries,
+    out: &str,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    refuse_untidy(out, &names::untidy(catalog, packages))?;
+    write_output(out, &render(catalog, packages, sort)?, dry_run)?;
+    if dry_run {
+        println!("{} packages would be written to {}", packages.len(), out);
+    } else {
+        println!("{} packages written to {}", packages.len(), out);
+    }
+    Ok(())
+}
+
+// Writing them back would either keep names that match nothing or
+// silently differ from the source
+fn refuse_untidy(out: &str, untidy: &[names::UntidyName]) -> Result<(), String> {
+    match untidy.first() {
+        Some(first) => Err(format!(
+            "Refusing to write {}: {} name fields have whitespace or control characters, \
+             e.g. {}: {}. Fix the source or pass --trim-names",
+            out,
+            untidy.len(),
+            first.location,
+            first
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Write `content` to `out`, or with `dry_run` print the diff against what
+/// is there now and leave the file alone
+pub fn write_output(out: &str, content: &str, dry_run: bool) -> std::io::Result<()> {
+    if !dry_run {
+        return fs::write(out, content);
+    }
+    let (existing, label) = match fs::read(out) {
+        Ok(bytes) => (String::from_utf8_lossy(&bytes).into_owned(), out),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => (String::new(), "/dev/null"),
+        Err(err) => return Err(err),
+    };
+    let patch = diff::unified(&existing, content, label, out);
+    if patch.is_empty() {
+        println!("{} would be left unchanged", out);
+    } else {
+        print!("{}", patch);
+    }
+    Ok(())
+}
+
+/// Copy the documents of selected packages from the source files to `out`
+/// byte for byte, one document in memory at a time. Untidy names are refused
+/// like write_catalog does. Returns the packages that had any document.
+pub fn stream_extract(
+    files: &[String],
+    selected: impl Fn(&str) -> bool,
+    limits: &LoadLimits,
+    out: &str,
+    dry_run: bool,
+) -> Result<BTreeSet<String>, Box<dyn std::error::Error>> {
+    let mut seen = BTreeSet::new();
+    if dry_run {
+        // A dry run keeps the output in memory to diff it at the end
+        let mut buffer = Vec::new();
+        let mut untidy = Vec::new();
+        copy_documents(
+            files,
+            &selected,
+            limits,
+            &mut buffer,
+            &mut seen,
+            &mut untidy,
+        )?;
+        refuse_untidy(out, &untidy)?;
+        write_output(out, &String::from_utf8_lossy(&buffer), true)?;
+        println!("{} packages would be written to {}", seen.len(), out);
+        return Ok(seen);
+    }
+
+    // Written next to `out` and renamed once complete, `out` may well be
+    // one of the files being read
+    let partial = format!("{}.partial-{}", out, std::process::id());
+    let mut writer = BufWriter::new(File::create(&partial)?);
+    let mut untidy = Vec::new();
+    let copied = copy_documents(
+        files,
+        &selected,
+        limits,
+        &mut writer,
+        &mut seen,
+        &mut untidy,
+    )
+    .and_then(|()| refuse_untidy(out, &untidy))
+    .and_then(|()| {
+        writer
+            .flush()
+            .map_err(|err| format!("Failed to write {}: {}", out, err))
+    });
+    drop(writer);
+    let written = copied.and_then(|()| {
+        fs::rename(&partial, out).map_err(|err| format!("Failed to write {}: {}", out, err))
+    });
+    if let Err(err) = written {
+        let _ = fs::remove_file(&partial);
+        return Err(err.into());
+    }
+    println!("{} packages written to {}", seen.len(), out);
+    Ok(seen)
+}
+
+fn copy_documents(
+    files: &[String],
+    selected: &impl Fn(&str) -> bool,
+    limits: &LoadLimits,
+    writer: &mut impl Write,
+    seen: &mut BTreeSet<String>,
+    untidy: &mut Vec<names::UntidyName>,
+) -> Result<(), String> {
+    for file in files {
+        loader::stream_documents(file, limits, |content| {
+            let Some(package) = loader::document_package(content) else {
+                return Ok(());
+            };
+            if !selected(&package) {
+                return Ok(());
+            }
+            seen.insert(package);
+            // Documents of unknown schemas are copied unchecked, like loading skips them
+            if let Ok(entry) = serde_yaml::from_slice::<CatalogEntry>(content) {
+                untidy.extend(names::check(&entry));
+            }
+            // Content after the `---` of the document's marker line comes with it
+            if content.first().is_some_and(u8::is_ascii_whitespace) {
+                writer.write_all(b"---")?;
+            } else {
+                writer.write_all(b"---\n")?;
+            }
+            writer.write_all(content)?;
+            if !content.ends_with(b"\n") {
+                writer.write_all(b"\n")?;
+            }
+            Ok(())
+        })
+        .map_err(|err| format!("Failed to read {}: {}", file, err))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+
+#[test]
+fn closed_stdout_ends_the_process_quietly() {
+    let dir = std::env::temp_dir().join(format!("catalog-browser-pipe-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    // Far more output than a pipe buffers
+    let catalog: String = (0..20_000)
+        .map(|index| format!("---\nschema: olm.package\nname: package-{}\n", index))
+        .collect();
+    fs::write(dir.join("catalog.yaml"), catalog).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_catalog_browser"))
+        .current_dir(&dir)
+        .args(["--no-pager", "-f", "catalog.yaml", "list", "packages"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("binary runs");
+    let mut first = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut first)
+        .unwrap();
+    assert_eq!(first, "Packages:\n");
+    let output = child.wait_with_output().unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+    assert_ne!(output.status.code(), Some(101));
+    fs::remove_dir_all(dir).unwrap();
+}
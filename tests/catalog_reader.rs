@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+
+use catalog_browser::catalog::CatalogEntry;
+use catalog_browser::loader::{CatalogReader, LoadLimits};
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn reads_every_catalog_file_of_a_directory_in_order() {
+    let dir = scratch("catalog-reader-directory");
+    fs::create_dir_all(dir.join("b")).unwrap();
+    fs::write(dir.join("a.yaml"), "schema: olm.package\nname: a\n").unwrap();
+    fs::write(
+        dir.join("b/catalog.json"),
+        "{\"schema\": \"olm.package\", \"name\": \"b\"}\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("notes.txt"),
+        "schema: olm.package\nname: ignored\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join(".hidden.yaml"),
+        "schema: olm.package\nname: hidden\n",
+    )
+    .unwrap();
+
+    let limits = LoadLimits::default();
+    let path = dir.to_string_lossy().into_owned();
+    let documents: Vec<(String, String)> = CatalogReader::open(&path, &limits)
+        .expect("directory opens")
+        .map(|item| {
+            let (meta, entry) = item.expect("valid document");
+            let CatalogEntry::OlmPackage(package) = entry else {
+                panic!("not a package at {}", meta.source);
+            };
+            let file = meta
+                .source
+                .strip_prefix(&path)
+                .unwrap_or(&meta.source)
+                .to_string();
+            (file, package.name)
+        })
+        .collect();
+    assert_eq!(
+        documents,
+        [
+            ("/a.yaml".to_string(), "a".to_string()),
+            ("/b/catalog.json".to_string(), "b".to_string()),
+        ]
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stops_iterating_when_dropped() {
+    let dir = scratch("catalog-reader-early");
+    let content: String = (0..100)
+        .map(|index| format!("---\nschema: olm.package\nname: p{}\n", index))
+        .collect();
+    fs::write(dir.join("catalog.yaml"), content).unwrap();
+
+    let limits = LoadLimits::default();
+    let path = dir.join("catalog.yaml").to_string_lossy().into_owned();
+    let first: Vec<u64> = CatalogReader::open(&path, &limits)
+        .expect("file opens")
+        .take(3)
+        .map(|item| item.expect("valid document").0.index)
+        .collect();
+    assert_eq!(first, [0, 1, 2]);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reads_the_packages_the_binary_lists() {
+    let dir = scratch("catalog-reader-listed");
+    fixture(&dir, "catalog.yaml");
+
+    let limits = LoadLimits::default();
+    let path = dir.join("catalog.yaml").to_string_lossy().into_owned();
+    let mut read: Vec<String> = CatalogReader::open(&path, &limits)
+        .expect("file opens")
+        .filter_map(|item| match item.expect("valid document").1 {
+            CatalogEntry::OlmPackage(package) => Some(format!("- {}", package.name)),
+            _ => None,
+        })
+        .collect();
+    read.sort();
+
+    let listed = run(&dir, &["-f", "catalog.yaml", "list", "packages"]);
+    assert_eq!(stdout(&listed), format!("Packages:\n{}\n", read.join("\n")));
+    fs::remove_dir_all(dir).unwrap();
+}
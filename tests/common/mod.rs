@@ -0,0 +1,37 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Empty directory of a test, removed first if an earlier run left it
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "catalog-browser-test-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("scratch directory");
+    dir
+}
+
+/// Copy a catalog of src/selftest into `dir`
+pub fn fixture(dir: &Path, name: &str) {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/selftest")
+        .join(name);
+    fs::copy(source, dir.join(name)).expect("fixture copied");
+}
+
+/// Run the binary in `dir` without a pager
+pub fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_catalog_browser"))
+        .current_dir(dir)
+        .arg("--no-pager")
+        .args(args)
+        .output()
+        .expect("binary runs")
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
@@ -0,0 +1,40 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn list_packages(fixture: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_catalog_browser"))
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/encodings"))
+        .args(["--no-pager", "-f", fixture, "list", "packages"])
+        .output()
+        .expect("binary runs")
+}
+
+#[test]
+fn every_encoding_fixture_lists_the_same_packages() {
+    for fixture in ["utf8-bom.yaml", "utf16le.yaml", "utf16be.yaml", "crlf.yaml"] {
+        let listed = list_packages(fixture);
+        assert!(listed.status.success(), "{}", fixture);
+        assert_eq!(
+            String::from_utf8_lossy(&listed.stdout),
+            "Packages:\n- encoded\n",
+            "{}",
+            fixture
+        );
+    }
+
+    let transcoded = list_packages("utf16be.yaml");
+    assert!(String::from_utf8_lossy(&transcoded.stderr)
+        .contains("utf16be.yaml is UTF-16BE, transcoded to UTF-8"));
+}
+
+#[test]
+fn broken_utf16_names_the_file_and_the_encoding() {
+    let listed = list_packages("utf16le-truncated.yaml");
+    assert!(!listed.status.success());
+    let stderr = String::from_utf8_lossy(&listed.stderr);
+    assert!(
+        stderr.contains("utf16le-truncated.yaml: truncated UTF-16LE content"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{fixture, run, scratch, stdout};
+
+const UNSORTED: &str = include_str!("fixtures/unsorted.yaml");
+
+/// Entry names of the channel documents of a written catalog, in file order
+fn entry_names(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.strip_prefix("- name: unsorted."))
+        .map(str::to_string)
+        .collect()
+}
+
+const AUTHORED: [&str; 5] = ["v1.10.0", "v1.0.0", "hotfix", "v1.2.0", "v1.1.0"];
+
+#[test]
+fn extract_keeps_authored_order_through_a_round_trip() {
+    let dir = scratch("entry-order-extract");
+    fs::write(dir.join("unsorted.yaml"), UNSORTED).unwrap();
+
+    let first = run(
+        &dir,
+        &[
+            "-f",
+            "unsorted.yaml",
+            "extract",
+            "--package",
+            "unsorted",
+            "--out",
+            "first.yaml",
+        ],
+    );
+    assert!(first.status.success(), "{}", stdout(&first));
+    assert_eq!(entry_names(&dir.join("first.yaml")), AUTHORED);
+
+    let second = run(
+        &dir,
+        &[
+            "-f",
+            "first.yaml",
+            "extract",
+            "--package",
+            "unsorted",
+            "--out",
+            "second.yaml",
+        ],
+    );
+    assert!(second.status.success(), "{}", stdout(&second));
+    assert_eq!(
+        fs::read_to_string(dir.join("first.yaml")).unwrap(),
+        fs::read_to_string(dir.join("second.yaml")).unwrap()
+    );
+}
+
+#[test]
+fn merge_keeps_authored_order() {
+    let dir = scratch("entry-order-merge");
+    fs::write(dir.join("unsorted.yaml"), UNSORTED).unwrap();
+    fixture(&dir, "catalog.yaml");
+
+    let merged = run(
+        &dir,
+        &[
+            "-f",
+            "unsorted.yaml",
+            "merge",
+            "--with",
+            "catalog.yaml",
+            "--out",
+            "merged.yaml",
+        ],
+    );
+    assert!(merged.status.success(), "{}", stdout(&merged));
+    assert_eq!(entry_names(&dir.join("merged.yaml")), AUTHORED);
+}
+
+#[test]
+fn semver_sorts_versioned_entries_and_keeps_the_rest_last() {
+    let dir = scratch("entry-order-semver");
+    fs::write(dir.join("unsorted.yaml"), UNSORTED).unwrap();
+
+    for command in [
+        &["extract", "--package", "unsorted"][..],
+        &["merge", "--with", "unsorted.yaml"][..],
+    ] {
+        let mut args = vec!["-f", "unsorted.yaml"];
+        args.extend(command);
+        args.extend(["--sort-entries", "semver", "--out", "sorted.yaml"]);
+        let sorted = run(&dir, &args);
+        assert!(sorted.status.success(), "{}", stdout(&sorted));
+        assert_eq!(
+            entry_names(&dir.join("sorted.yaml")),
+            ["v1.0.0", "v1.1.0", "v1.2.0", "v1.10.0", "hotfix"]
+        );
+    }
+}
+
+#[test]
+fn lists_are_ordered_by_package_name() {
+    let dir = scratch("entry-order-list");
+    let packages = [
+        "zeta", "alpha", "mu", "beta", "omega", "kappa", "delta", "eta",
+    ];
+    let catalog: String = packages
+        .iter()
+        .map(|package| {
+            format!(
+                "---\nschema: olm.package\nname: {0}\n---\nschema: olm.channel\n\
+                 package: {0}\nname: {0}-stable\nentries:\n  - name: {0}.v1.0.0\n\
+                 ---\nschema: olm.bundle\npackage: {0}\nname: {0}.v1.0.0\n\
+                 image: quay.io/example/{0}:1.0.0\n",
+                package
+            )
+        })
+        .collect();
+    fs::write(dir.join("catalog.yaml"), catalog).unwrap();
+    let mut sorted = packages;
+    sorted.sort();
+
+    let listed = |kind: &str, suffix: &str| -> String {
+        let names: String = sorted
+            .iter()
+            .map(|package| format!("- {}{}\n", package, suffix))
+            .collect();
+        format!("{}:\n{}", kind, names)
+    };
+    for (content, expected) in [
+        ("packages", listed("Packages", "")),
+        ("channels", listed("Channels", "-stable")),
+        ("bundles", listed("Bundles", ".v1.0.0")),
+    ] {
+        let output = run(&dir, &["-f", "catalog.yaml", "list", content]);
+        assert_eq!(stdout(&output), expected);
+    }
+
+    let json = run(
+        &dir,
+        &["-f", "catalog.yaml", "list", "bundles", "--output", "json"],
+    );
+    let json: serde_json::Value = serde_json::from_str(&stdout(&json)).unwrap();
+    let names: Vec<&str> = json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["package"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, sorted);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use catalog_browser::examples::EXAMPLES;
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn every_example_answers_its_question() {
+    let dir = scratch("examples");
+    for file in fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("src/selftest")).unwrap() {
+        fixture(&dir, file.unwrap().file_name().to_str().unwrap());
+    }
+
+    for example in EXAMPLES {
+        let args: Vec<&str> = example.args.split_whitespace().collect();
+        let output = run(&dir, &args);
+        assert!(
+            output.status.success(),
+            "'{}' failed: {}",
+            example.command_line(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(
+            stdout(&output).contains(example.expect),
+            "'{}' printed no '{}':\n{}",
+            example.command_line(),
+            example.expect,
+            stdout(&output)
+        );
+    }
+}
+
+#[test]
+fn examples_are_listed_and_in_the_subcommand_help() {
+    let dir = scratch("examples-help");
+    let listed = stdout(&run(&dir, &["examples"]));
+    for example in EXAMPLES {
+        assert!(
+            listed.contains(&example.command_line()),
+            "{}",
+            example.question
+        );
+
+        let help = stdout(&run(&dir, &[example.subcommand(), "--help"]));
+        assert!(
+            help.contains(&format!(
+                "# {}\n  {}",
+                example.question,
+                example.command_line()
+            )),
+            "{} --help lacks '{}'",
+            example.subcommand(),
+            example.question
+        );
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn stream_extract_opens_up_json_streams() {
+    let dir = scratch("stream-json");
+    fixture(&dir, "catalog.json");
+    let streamed = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.json",
+            "extract",
+            "--stream",
+            "--package",
+            "demo",
+            "--out",
+            "streamed.yaml",
+        ],
+    );
+    assert!(streamed.status.success(), "{}", stdout(&streamed));
+    assert!(stdout(&streamed).contains("1 packages written to streamed.yaml"));
+
+    let loaded = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.json",
+            "extract",
+            "--package",
+            "demo",
+            "--out",
+            "loaded.yaml",
+        ],
+    );
+    assert!(loaded.status.success());
+    let compared = run(
+        &dir,
+        &[
+            "-f",
+            "streamed.yaml",
+            "-f",
+            "loaded.yaml",
+            "--require-identical",
+            "list",
+            "bundles",
+        ],
+    );
+    assert!(
+        compared.status.success(),
+        "streamed and loaded extracts differ"
+    );
+    assert!(stdout(&compared).contains("- demo.v1.2.0"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stream_extract_opens_up_config_maps() {
+    let dir = scratch("stream-configmap");
+    let catalog = fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/selftest/catalog.yaml"),
+    )
+    .unwrap();
+    let config_map = serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "ConfigMap",
+        "metadata": {"name": "catalog"},
+        "data": {"catalog.yaml": catalog},
+    });
+    fs::write(dir.join("configmap.json"), config_map.to_string()).unwrap();
+    let output = run(
+        &dir,
+        &[
+            "-f",
+            "configmap.json",
+            "extract",
+            "--stream",
+            "--package",
+            "demo-base",
+            "--out",
+            "out.yaml",
+        ],
+    );
+    assert!(output.status.success(), "{}", stdout(&output));
+    let written = fs::read_to_string(dir.join("out.yaml")).unwrap();
+    assert!(written.contains("name: demo-base") && !written.contains("name: demo\n"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stream_extract_can_write_over_its_input() {
+    let dir = scratch("stream-in-place");
+    fixture(&dir, "catalog.yaml");
+    let output = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "extract",
+            "--stream",
+            "--package",
+            "demo",
+            "--out",
+            "catalog.yaml",
+        ],
+    );
+    assert!(output.status.success(), "{}", stdout(&output));
+    let listed = run(&dir, &["-f", "catalog.yaml", "list", "packages"]);
+    assert_eq!(stdout(&listed), "Packages:\n- demo\n");
+    let leftovers: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(leftovers.len(), 1, "partial output left behind");
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn labels_relabel_package_and_bundle_views() {
+    let dir = scratch("labels");
+    fixture(&dir, "catalog.yaml");
+    fs::write(
+        dir.join("labels.txt"),
+        "# German\npackage=Paket\ndefault_channel=Standardkanal\nbundle=Bündel\nproperties=Eigenschaften\n",
+    )
+    .unwrap();
+
+    let package = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "--labels",
+            "labels.txt",
+            "show",
+            "package",
+            "demo",
+        ],
+    );
+    let package = stdout(&package);
+    assert!(
+        package.starts_with("Paket: demo\n  Standardkanal: stable\n"),
+        "{}",
+        package
+    );
+
+    let bundle = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "--labels",
+            "labels.txt",
+            "show",
+            "bundle",
+            "demo.v1.0.0",
+        ],
+    );
+    let bundle = stdout(&bundle);
+    assert!(bundle.starts_with("Bündel: demo.v1.0.0\n"), "{}", bundle);
+    assert!(
+        bundle.contains("\n  Paket: demo\n  Eigenschaften:\n    - olm.package: "),
+        "{}",
+        bundle
+    );
+    assert!(!bundle.contains("Property {"), "{}", bundle);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn unknown_labels_are_rejected() {
+    let dir = scratch("labels-unknown");
+    fixture(&dir, "catalog.yaml");
+    fs::write(dir.join("labels.txt"), "packgae=Paket\n").unwrap();
+    let output = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "--labels",
+            "labels.txt",
+            "list",
+            "packages",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown label 'packgae'"));
+    assert!(stdout(&output).is_empty());
+    fs::remove_dir_all(dir).unwrap();
+}
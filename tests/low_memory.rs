@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn low_memory_output_matches_a_full_load() {
+    let dir = scratch("low-memory");
+    fixture(&dir, "catalog.yaml");
+    for args in [
+        ["list", "packages", "--output", "json"],
+        ["list", "channels", "--output", "json"],
+        ["list", "bundles", "--output", "json"],
+        ["list", "bundles", "--output", "text"],
+    ] {
+        let mut full = vec!["-f", "catalog.yaml"];
+        full.extend(args);
+        let mut low = full.clone();
+        low.push("--low-memory");
+        let (full, low) = (run(&dir, &full), run(&dir, &low));
+        assert!(low.status.success(), "{:?} failed", args);
+        assert_eq!(stdout(&full), stdout(&low), "{:?} differs", args);
+        assert!(String::from_utf8_lossy(&low.stderr).contains("low-memory: retained"));
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn low_memory_refuses_commands_needing_what_it_drops() {
+    let dir = scratch("low-memory-refused");
+    fixture(&dir, "catalog.yaml");
+    for args in [vec!["dump"], vec!["stats", "--largest", "3"]] {
+        let mut command = vec!["-f", "catalog.yaml", "--low-memory"];
+        command.extend(args.iter().copied());
+        let output = run(&dir, &command);
+        assert!(!output.status.success(), "{:?} ran", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Run it without --low-memory"));
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn low_memory_extract_streams_json_catalogs() {
+    let dir = scratch("low-memory-extract");
+    fixture(&dir, "catalog.json");
+    let output = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.json",
+            "--low-memory",
+            "extract",
+            "--package",
+            "demo",
+            "--out",
+            "demo.yaml",
+        ],
+    );
+    assert!(output.status.success(), "{}", stdout(&output));
+    let listed = run(&dir, &["-f", "demo.yaml", "list", "bundles"]);
+    assert!(stdout(&listed).contains("- demo.v1.2.0"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn low_memory_extract_refuses_untidy_names() {
+    let dir = scratch("low-memory-untidy");
+    fixture(&dir, "untidy.yaml");
+    for mode in ["--low-memory", "--stream"] {
+        let mut command = vec!["-f", "untidy.yaml"];
+        if mode == "--low-memory" {
+            command.extend([mode, "extract"]);
+        } else {
+            command.extend(["extract", mode]);
+        }
+        command.extend(["--package", "etcd", "--out", "etcd.yaml"]);
+        let output = run(&dir, &command);
+        assert!(!output.status.success(), "{} wrote untidy names", mode);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Refusing to write etcd.yaml: 3 name fields"),
+            "{}",
+            stderr
+        );
+        assert!(
+            !dir.join("etcd.yaml").exists(),
+            "{} left output behind",
+            mode
+        );
+    }
+    let leftovers: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(leftovers.len(), 1, "partial output left behind");
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn catalogs_in_annotations_load_and_stream() {
+    let dir = scratch("manifests-annotation");
+    fixture(&dir, "catalog.yaml");
+    let catalog = fs::read_to_string(dir.join("catalog.yaml")).unwrap();
+    let catalog_source = serde_json::json!({
+        "apiVersion": "operators.coreos.com/v1alpha1",
+        "kind": "CatalogSource",
+        "metadata": {
+            "name": "demo-index",
+            "annotations": {"example.com/rendered-catalog": catalog, "example.com/owner": "team"},
+        },
+        "spec": {"sourceType": "grpc", "image": "quay.io/example/demo-index:1"},
+    });
+    fs::write(dir.join("catalogsource.json"), catalog_source.to_string()).unwrap();
+
+    let listed = run(&dir, &["-f", "catalogsource.json", "list", "packages"]);
+    assert!(listed.status.success());
+    let mut packages: Vec<_> = stdout(&listed).lines().map(str::to_string).collect();
+    packages.sort();
+    assert_eq!(packages, ["- demo", "- demo-base", "Packages:"]);
+    assert!(
+        listed.stderr.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&listed.stderr)
+    );
+
+    let streamed = run(
+        &dir,
+        &[
+            "-f",
+            "catalogsource.json",
+            "extract",
+            "--stream",
+            "--package",
+            "demo-base",
+            "--out",
+            "out.yaml",
+        ],
+    );
+    assert!(streamed.status.success(), "{}", stdout(&streamed));
+    assert!(fs::read_to_string(dir.join("out.yaml"))
+        .unwrap()
+        .contains("name: demo-base\n"));
+}
+
+#[test]
+fn grpc_catalog_sources_without_a_catalog_report_their_image() {
+    let dir = scratch("manifests-image");
+    fixture(&dir, "catalog.yaml");
+    fs::write(
+        dir.join("catalogsource.yaml"),
+        "apiVersion: operators.coreos.com/v1alpha1\nkind: CatalogSource\nmetadata:\n  name: demo-index\nspec:\n  sourceType: grpc\n  image: quay.io/example/demo-index:1\n",
+    )
+    .unwrap();
+    let listed = run(
+        &dir,
+        &[
+            "-f",
+            "catalogsource.yaml",
+            "-f",
+            "catalog.yaml",
+            "list",
+            "packages",
+        ],
+    );
+    assert!(listed.status.success());
+    let stderr = String::from_utf8_lossy(&listed.stderr);
+    assert!(
+        stderr.contains(
+            "CatalogSource 'demo-index' serves the catalog image quay.io/example/demo-index:1"
+        ),
+        "{}",
+        stderr
+    );
+}
+
+/// PATH holding nothing but an `opm` that renders `image` to the demo
+/// catalog, so a real opm can't stand in for it
+fn fake_opm(dir: &std::path::Path, image: &str) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = dir.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let script = format!(
+        "#!/bin/sh\n\
+         if [ \"$*\" != \"render {} --output yaml\" ]; then echo \"unexpected: $*\" >&2; exit 1; fi\n\
+         echo rendering >&2\n\
+         exec /bin/cat catalog.yaml\n",
+        image
+    );
+    fs::write(bin.join("opm"), script).unwrap();
+    fs::set_permissions(bin.join("opm"), fs::Permissions::from_mode(0o755)).unwrap();
+    bin.display().to_string()
+}
+
+#[test]
+fn follow_image_loads_the_rendered_catalog_source_image() {
+    let dir = scratch("manifests-follow-image");
+    fixture(&dir, "catalog.yaml");
+    fs::write(
+        dir.join("catalogsource.yaml"),
+        "apiVersion: operators.coreos.com/v1alpha1\nkind: CatalogSource\nmetadata:\n  name: demo-index\nspec:\n  sourceType: grpc\n  image: quay.io/example/demo-index:1\n",
+    )
+    .unwrap();
+    let path = fake_opm(&dir, "quay.io/example/demo-index:1");
+    let follow = |extra: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_catalog_browser"))
+            .current_dir(&dir)
+            .env("PATH", &path)
+            .args(["--no-pager", "-f", "catalogsource.yaml"])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    let listed = follow(&["--follow-image", "list", "packages"]);
+    let stderr = String::from_utf8_lossy(&listed.stderr);
+    assert!(listed.status.success(), "{}", stderr);
+    assert_eq!(stdout(&listed), "Packages:\n- demo\n- demo-base\n");
+    assert!(stderr.contains("opm: rendering"), "{}", stderr);
+
+    // Without the flag opm isn't run
+    let reported = follow(&["list", "packages"]);
+    assert_eq!(stdout(&reported), "Packages:\n");
+    assert!(String::from_utf8_lossy(&reported.stderr).contains("pass --follow-image"));
+
+    let streamed = follow(&[
+        "--follow-image",
+        "extract",
+        "--stream",
+        "--package",
+        "demo",
+        "--out",
+        "out.yaml",
+    ]);
+    assert!(!streamed.status.success());
+    assert!(!dir.join("out.yaml").exists());
+
+    fs::remove_file(dir.join("bin/opm")).unwrap();
+    let missing = follow(&["--follow-image", "list", "packages"]);
+    assert!(!missing.status.success());
+    assert!(
+        String::from_utf8_lossy(&missing.stderr).contains("opm not found on PATH"),
+        "{}",
+        String::from_utf8_lossy(&missing.stderr)
+    );
+}
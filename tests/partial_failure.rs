@@ -0,0 +1,170 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture, run, scratch, stdout};
+
+const PARTIAL_FAILURE: i32 = 5;
+
+fn stderr(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn show_from_file_exits_5_when_some_names_are_missing() {
+    let dir = scratch("partial-show");
+    fixture(&dir, "catalog.yaml");
+    fs::write(dir.join("some.txt"), "demo\n# comment\nnope\n").unwrap();
+    fs::write(dir.join("none.txt"), "nope\n").unwrap();
+
+    let some = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "show",
+            "package",
+            "--from-file",
+            "some.txt",
+        ],
+    );
+    assert_eq!(some.status.code(), Some(PARTIAL_FAILURE));
+    assert!(
+        stdout(&some).starts_with("Package: demo\n"),
+        "{}",
+        stdout(&some)
+    );
+    assert!(
+        stderr(&some).contains("1 of 2 names succeeded, 1 failed"),
+        "{}",
+        stderr(&some)
+    );
+
+    let none = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "show",
+            "package",
+            "--from-file",
+            "none.txt",
+        ],
+    );
+    assert_eq!(none.status.code(), Some(1));
+}
+
+#[test]
+fn extract_exits_5_when_some_packages_are_missing() {
+    let dir = scratch("partial-extract");
+    fixture(&dir, "catalog.yaml");
+
+    for (stream, out) in [(false, "loaded.yaml"), (true, "streamed.yaml")] {
+        let mut args = vec![
+            "-f",
+            "catalog.yaml",
+            "extract",
+            "--package",
+            "demo",
+            "--package",
+            "nope",
+            "--out",
+            out,
+        ];
+        if stream {
+            args.push("--stream");
+        }
+        let extracted = run(&dir, &args);
+        assert_eq!(
+            extracted.status.code(),
+            Some(PARTIAL_FAILURE),
+            "stream {}: {}",
+            stream,
+            stderr(&extracted)
+        );
+        // What was found is still written
+        assert!(fs::read_to_string(dir.join(out))
+            .unwrap()
+            .contains("name: demo\n"));
+    }
+
+    let all = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "extract",
+            "--package",
+            "demo",
+            "--out",
+            "all.yaml",
+        ],
+    );
+    assert_eq!(all.status.code(), Some(0));
+}
+
+#[test]
+fn merge_exits_5_when_some_files_fail_to_load() {
+    let dir = scratch("partial-merge");
+    fixture(&dir, "catalog.yaml");
+    fixture(&dir, "previous.yaml");
+
+    let merged = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "merge",
+            "--with",
+            "previous.yaml",
+            "--with",
+            "missing.yaml",
+            "--out",
+            "merged.yaml",
+        ],
+    );
+    assert_eq!(
+        merged.status.code(),
+        Some(PARTIAL_FAILURE),
+        "{}",
+        stderr(&merged)
+    );
+    assert!(
+        stderr(&merged).contains("missing.yaml"),
+        "{}",
+        stderr(&merged)
+    );
+    assert!(dir.join("merged.yaml").exists());
+}
+
+#[test]
+fn merge_leaves_the_output_alone_when_every_file_fails() {
+    let dir = scratch("partial-merge-none");
+    fixture(&dir, "catalog.yaml");
+    fs::write(dir.join("merged.yaml"), "left alone\n").unwrap();
+
+    let merged = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "merge",
+            "--with",
+            "missing.yaml",
+            "--with",
+            "gone.yaml",
+            "--out",
+            "merged.yaml",
+        ],
+    );
+    assert_eq!(merged.status.code(), Some(1), "{}", stderr(&merged));
+    assert!(
+        stderr(&merged).contains("all 2 files failed"),
+        "{}",
+        stderr(&merged)
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("merged.yaml")).unwrap(),
+        "left alone\n"
+    );
+}
@@ -0,0 +1,242 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{fixture, run, scratch, stdout};
+use serde_json::Value;
+
+/// Every `schema` document, with a command printing it against the selftest
+/// catalogs. The warnings document goes to stderr and has its own test.
+const DOCUMENTS: [(&str, &[&str]); 19] = [
+    ("list-packages", &["list", "packages"]),
+    ("list-channels", &["list", "channels"]),
+    ("list-bundles", &["list", "bundles"]),
+    ("show-package", &["show", "package", "demo"]),
+    ("show-channel", &["show", "channel", "demo"]),
+    ("show-bundle", &["show", "bundle", "demo.v1.2.0"]),
+    ("validate", &["validate"]),
+    ("validate-groups", &["validate", "--group-by", "rule"]),
+    ("stats", &["stats"]),
+    ("health", &["health"]),
+    (
+        "show-ocp-matrix",
+        &["show", "package", "demo", "--ocp-matrix"],
+    ),
+    (
+        "show-annotations",
+        &["show", "package", "demo", "--annotations"],
+    ),
+    (
+        "show-from-file",
+        &["show", "package", "--from-file", "names.txt"],
+    ),
+    ("apis", &["apis"]),
+    ("apis-conflicts", &["apis", "--conflicts"]),
+    ("dump", &["dump"]),
+    ("stats-largest", &["stats", "--largest"]),
+    (
+        "analyze-duplicates",
+        &["-f", "rebrand.yaml", "analyze", "duplicates"],
+    ),
+    ("warnings", &[]),
+];
+
+/// A package shipping the demo bundle image, so analyze duplicates has a pair
+const REBRAND: &str = "\
+schema: olm.package
+name: rebrand
+defaultChannel: stable
+---
+schema: olm.channel
+package: rebrand
+name: stable
+entries:
+  - name: rebrand.v1.0.0
+---
+schema: olm.bundle
+name: rebrand.v1.0.0
+package: rebrand
+image: quay.io/example/demo-bundle@sha256:1000000000000000000000000000000000000000000000000000000000000000
+properties:
+  - type: olm.package
+    value:
+      packageName: rebrand
+      version: 1.0.0
+";
+
+fn snapshot(document: &str) -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/schemas/v1")
+        .join(format!("{}.json", document));
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Check `value` against the parts of JSON Schema schemars generates:
+/// references, anyOf, const, enum, type, properties and items
+fn check(value: &Value, schema: &Value, root: &Value, path: &str) -> Result<(), String> {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/$defs/");
+        return check(value, &root["$defs"][name], root, path);
+    }
+    if let Some(alternatives) = schema["anyOf"].as_array().or(schema["oneOf"].as_array()) {
+        let failures: Vec<String> = alternatives
+            .iter()
+            .filter_map(|alternative| check(value, alternative, root, path).err())
+            .collect();
+        if failures.len() == alternatives.len() {
+            return Err(format!("{}: matches no alternative: {:?}", path, failures));
+        }
+    }
+    if let Some(all) = schema["allOf"].as_array() {
+        for part in all {
+            check(value, part, root, path)?;
+        }
+    }
+    if !schema["const"].is_null() && schema["const"] != *value {
+        return Err(format!("{}: {} isn't {}", path, value, schema["const"]));
+    }
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            return Err(format!("{}: {} not in {:?}", path, value, allowed));
+        }
+    }
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(name) => vec![name.as_str()],
+        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let type_matches = |name: &str| match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    };
+    if !types.is_empty() && !types.iter().any(|&name| type_matches(name)) {
+        return Err(format!("{}: {} isn't of type {:?}", path, value, types));
+    }
+
+    if let Value::Object(object) = value {
+        let properties = schema["properties"].as_object();
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let required = required.as_str().unwrap();
+            if !object.contains_key(required) {
+                return Err(format!("{}: missing {}", path, required));
+            }
+        }
+        for (key, field) in object {
+            let field_path = format!("{}.{}", path, key);
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property) => check(field, property, root, &field_path)?,
+                None => match &schema["additionalProperties"] {
+                    Value::Bool(false) => return Err(format!("{}: not in the schema", field_path)),
+                    Value::Object(_) => {
+                        check(field, &schema["additionalProperties"], root, &field_path)?
+                    }
+                    // Flattened maps allow any key, structs list theirs
+                    _ if properties.is_some() && schema["additionalProperties"].is_null() => {
+                        return Err(format!("{}: not in the schema", field_path))
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item)) = (value, schema.get("items")) {
+        for (index, element) in items.iter().enumerate() {
+            check(element, item, root, &format!("{}[{}]", path, index))?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn every_schema_document_is_covered() {
+    let dir = scratch("schemas-covered");
+    let help = stdout(&run(&dir, &["schema", "--help"]));
+    let listed: Vec<&str> = help
+        .lines()
+        .take_while(|line| !line.starts_with("Options:"))
+        .filter_map(|line| line.trim_start().strip_prefix("- "))
+        .filter_map(|line| line.split(':').next())
+        .collect();
+    let covered: Vec<&str> = DOCUMENTS.iter().map(|(document, _)| *document).collect();
+    assert_eq!(listed, covered);
+}
+
+// A changed shape breaks consumers pinned to catalog-browser/v1. Adding
+// optional fields is fine and only needs the snapshot refreshed, anything
+// else needs a new apiVersion.
+#[test]
+fn schemas_match_the_v1_snapshots() {
+    let dir = scratch("schemas");
+    for (document, _) in DOCUMENTS {
+        let printed = run(&dir, &["schema", document]);
+        assert!(printed.status.success(), "schema {}", document);
+        let printed: Value = serde_json::from_str(&stdout(&printed)).unwrap();
+        assert_eq!(printed, snapshot(document), "schema {} changed", document);
+        assert_eq!(
+            printed["properties"]["apiVersion"]["const"],
+            "catalog-browser/v1"
+        );
+    }
+}
+
+#[test]
+fn printed_documents_match_their_schema() {
+    let dir = scratch("schemas-documents");
+    fixture(&dir, "catalog.yaml");
+    fs::write(dir.join("names.txt"), "demo\nnope\n").unwrap();
+    fs::write(dir.join("rebrand.yaml"), REBRAND).unwrap();
+    for (document, command) in DOCUMENTS.iter().filter(|(_, command)| !command.is_empty()) {
+        let mut args = vec!["-f", "catalog.yaml"];
+        args.extend(*command);
+        args.extend(["--output", "json"]);
+        let printed = stdout(&run(&dir, &args));
+        let printed: Value = serde_json::from_str(&printed)
+            .unwrap_or_else(|err| panic!("{}: {}: {}", document, err, printed));
+        let schema = snapshot(document);
+        if let Err(err) = check(&printed, &schema, &schema, document) {
+            panic!("{}", err);
+        }
+        if let Some(items) = printed["items"].as_array() {
+            assert!(!items.is_empty(), "{} has no items to check", document);
+        }
+    }
+}
+
+#[test]
+fn api_version_pins_the_output_version() {
+    let dir = scratch("schemas-api-version");
+    fixture(&dir, "catalog.yaml");
+    let pinned = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "--api-version",
+            "catalog-browser/v1",
+            "list",
+            "packages",
+        ],
+    );
+    assert!(pinned.status.success());
+
+    let unsupported = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "--api-version",
+            "catalog-browser/v2",
+            "list",
+            "packages",
+        ],
+    );
+    assert!(!unsupported.status.success());
+    assert!(String::from_utf8_lossy(&unsupported.stderr).contains("unsupported --api-version"));
+}
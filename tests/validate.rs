@@ -0,0 +1,66 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn fail_fast_stops_at_the_same_error_every_run() {
+    let dir = scratch("validate-fail-fast");
+    // Every package has a bundle with a broken image reference
+    let packages = [
+        "zeta", "alpha", "mu", "beta", "omega", "kappa", "delta", "eta",
+    ];
+    let catalog: String = packages
+        .iter()
+        .map(|package| {
+            format!(
+                "---\nschema: olm.package\nname: {0}\n---\nschema: olm.bundle\n\
+                 package: {0}\nname: {0}.v1.0.0\nimage: 'quay.io/example/{0}::1.0.0'\n",
+                package
+            )
+        })
+        .collect();
+    fs::write(dir.join("catalog.yaml"), catalog).unwrap();
+
+    for _ in 0..5 {
+        let output = run(
+            &dir,
+            &[
+                "-f",
+                "catalog.yaml",
+                "validate",
+                "--fail-fast",
+                "--output",
+                "json",
+            ],
+        );
+        assert!(!output.status.success());
+        let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+        assert_eq!(report["cutShort"], true);
+        assert_eq!(report["findings"].as_array().unwrap().len(), 1);
+        assert_eq!(report["findings"][0]["location"], "alpha/alpha.v1.0.0");
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn fail_fast_runs_every_rule_without_errors() {
+    let dir = scratch("validate-fail-fast-clean");
+    fixture(&dir, "catalog.yaml");
+    let output = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "validate",
+            "--fail-fast",
+            "--output",
+            "json",
+        ],
+    );
+    assert!(output.status.success(), "{}", stdout(&output));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["cutShort"], false);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{fixture, run, scratch, stdout};
+use serde::Deserialize;
+
+const VENDOR_KEYS: &str = include_str!("fixtures/vendor-keys.yaml");
+
+/// The channel document of a written catalog, parsed
+fn channel(path: &Path) -> serde_yaml::Value {
+    let content = fs::read_to_string(path).unwrap();
+    let documents: Vec<serde_yaml::Value> = serde_yaml::Deserializer::from_str(&content)
+        .map(|document| serde_yaml::Value::deserialize(document).unwrap())
+        .collect();
+    documents
+        .into_iter()
+        .find(|document| document["schema"] == "olm.channel" && document["package"] == "vendored")
+        .expect("channel written")
+}
+
+fn assert_vendor_keys(path: &Path) {
+    let channel = channel(path);
+    assert_eq!(channel["priority"], 10);
+    assert_eq!(channel["displayName"], "Stable updates");
+    assert_eq!(channel["tracking"]["id"], "CHAN-42");
+    assert_eq!(channel["tracking"]["owners"][0], "team-a");
+}
+
+#[test]
+fn extract_keeps_vendor_keys_of_channels() {
+    let dir = scratch("vendor-keys-extract");
+    fs::write(dir.join("vendor-keys.yaml"), VENDOR_KEYS).unwrap();
+
+    let extracted = run(
+        &dir,
+        &[
+            "-f",
+            "vendor-keys.yaml",
+            "extract",
+            "--package",
+            "vendored",
+            "--out",
+            "extracted.yaml",
+        ],
+    );
+    assert!(extracted.status.success(), "{}", stdout(&extracted));
+    assert_vendor_keys(&dir.join("extracted.yaml"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn merge_keeps_vendor_keys_of_channels() {
+    let dir = scratch("vendor-keys-merge");
+    fs::write(dir.join("vendor-keys.yaml"), VENDOR_KEYS).unwrap();
+    fixture(&dir, "catalog.yaml");
+
+    // Both as the loaded catalog and as the one merged in
+    for (base, with) in [
+        ("vendor-keys.yaml", "catalog.yaml"),
+        ("catalog.yaml", "vendor-keys.yaml"),
+    ] {
+        let merged = run(
+            &dir,
+            &["-f", base, "merge", "--with", with, "--out", "merged.yaml"],
+        );
+        assert!(merged.status.success(), "{}", stdout(&merged));
+        assert_vendor_keys(&dir.join("merged.yaml"));
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
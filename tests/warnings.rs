@@ -0,0 +1,92 @@
+mod common;
+
+use common::{fixture, run, scratch, stdout};
+
+#[test]
+fn json_warnings_match_the_published_schema() {
+    let dir = scratch("warnings");
+    fixture(&dir, "catalog.yaml");
+    fixture(&dir, "previous.yaml");
+
+    let schema: serde_json::Value =
+        serde_json::from_str(&stdout(&run(&dir, &["schema", "warnings"]))).unwrap();
+    let warning = &schema["$defs"]["Warning"];
+    let properties = warning["properties"].as_object().unwrap();
+
+    let output = run(
+        &dir,
+        &[
+            "-f",
+            "catalog.yaml",
+            "-f",
+            "previous.yaml",
+            "--require-identical",
+            "--warnings-format",
+            "json",
+            "list",
+            "packages",
+        ],
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // The error line follows the warnings document
+    let document = &stderr[..stderr.rfind("\n}").expect("warnings document") + 2];
+    let document: serde_json::Value = serde_json::from_str(document).unwrap();
+    assert_eq!(document["kind"], schema["properties"]["kind"]["const"]);
+
+    let items = document["items"].as_array().unwrap();
+    assert_eq!(items[0]["code"], "catalog-different");
+    for item in items {
+        let item = item.as_object().unwrap();
+        for key in item.keys() {
+            assert!(properties.contains_key(key), "{} not in the schema", key);
+        }
+        for required in warning["required"].as_array().unwrap() {
+            assert!(item.contains_key(required.as_str().unwrap()));
+        }
+    }
+}
+
+#[test]
+fn require_identical_names_the_platforms_it_compared() {
+    let dir = scratch("require-identical");
+    fixture(&dir, "catalog.yaml");
+    fixture(&dir, "previous.yaml");
+
+    let identical = run(
+        &dir,
+        &[
+            "-f",
+            "amd64=catalog.yaml",
+            "-f",
+            "arm64=catalog.yaml",
+            "--require-identical",
+            "list",
+            "packages",
+        ],
+    );
+    assert!(identical.status.success());
+    assert!(String::from_utf8_lossy(&identical.stderr)
+        .contains("Catalog content identical across amd64, arm64"));
+
+    let different = run(
+        &dir,
+        &[
+            "-f",
+            "amd64=catalog.yaml",
+            "-f",
+            "arm64=previous.yaml",
+            "--require-identical",
+            "list",
+            "packages",
+        ],
+    );
+    assert!(!different.status.success());
+    let stderr = String::from_utf8_lossy(&different.stderr);
+    assert!(
+        stderr.contains("arm64 differs from amd64:\n")
+            && stderr.contains("\n  missing package demo-base\n"),
+        "{}",
+        stderr
+    );
+}